@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::response::status;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::serde::json::Json;
+use rocket::{Request, Route, State};
+use schemars::JsonSchema;
+
+use crate::config::AuthConfig;
+use crate::error::{self, ErrorCode, SimplexError};
+use crate::parse::{self, JsonBody};
+use crate::row_arithmetic::LinearProgram;
+use crate::store::Store;
+use crate::v1::{api_error, ApiResult};
+use crate::LinearProgramResponse;
+
+// a key from the keys file or the store, the limits that are left out are the ones from the [global.auth] table
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiKey
+{
+    pub name: String,
+    pub key: String,
+    // admin keys can add and remove keys and see the usage of every key
+    #[serde(default)]
+    pub admin: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_columns: Option<usize>
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", default)]
+pub struct KeyUsage
+{
+    pub name: String,
+    pub requests: u64,
+    // requests turned away because the key was over its rate limit, they are not in requests
+    pub rate_limited: u64,
+    pub solves: u64,
+    pub iterations: u64,
+    // wall clock time from the start to the end of each solve, a solve that waited for a core counts that time too
+    pub wall_time_us: u64
+}
+
+// requests are counted per minute of the clock, the count starts over when the minute changes
+struct Window
+{
+    minute: u64,
+    requests: u32,
+    refused: u32
+}
+
+pub struct Account
+{
+    key: ApiKey,
+    requests_per_minute: u32,
+    max_rows: usize,
+    max_columns: usize,
+    window: Mutex<Window>,
+    usage: Mutex<KeyUsage>,
+    // set when the usage changed after it was last written
+    unsaved: AtomicBool,
+    // held while the usage is written so an older copy never overwrites a newer one
+    saving: Mutex<()>,
+    usage_tree: Option<sled::Tree>
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T>
+{
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Account
+{
+    fn new(key: ApiKey, config: &AuthConfig, usage_tree: Option<sled::Tree>) -> Self
+    {
+        // usage written by an older version is started over rather than refusing the key
+        let usage = usage_tree.as_ref()
+            .and_then(|tree| tree.get(key.name.as_bytes()).ok().flatten())
+            .and_then(|bytes| serde_json::from_slice::<KeyUsage>(&bytes).ok())
+            .unwrap_or_else(|| KeyUsage { name: key.name.clone(), ..KeyUsage::default() });
+
+        Account
+        {
+            requests_per_minute: key.requests_per_minute.unwrap_or(config.requests_per_minute),
+            max_rows: key.max_rows.unwrap_or(config.max_rows),
+            max_columns: key.max_columns.unwrap_or(config.max_columns),
+            key: key,
+            window: Mutex::new(Window { minute: 0, requests: 0, refused: 0 }),
+            usage: Mutex::new(usage),
+            unsaved: AtomicBool::new(false),
+            saving: Mutex::new(()),
+            usage_tree: usage_tree
+        }
+    }
+
+    fn admit(&self, minute: u64) -> Result<(), SimplexError>
+    {
+        let mut window = lock(&self.window);
+
+        if window.minute != minute
+        {
+            *window = Window { minute: minute, requests: 0, refused: 0 };
+            // the usage of the minutes before is written when the key is next used, one write a minute at most
+            drop(window);
+            self.save_usage();
+            window = lock(&self.window);
+        }
+
+        if window.requests >= self.requests_per_minute
+        {
+            // found is every request made with the key this minute, this one and the ones refused before it included
+            window.refused += 1;
+            let made = (window.requests + window.refused) as usize;
+            drop(window);
+            self.update_usage(|usage| usage.rate_limited += 1);
+
+            return Err(SimplexError::new(ErrorCode::RateLimited,
+                format!("The key {} is over its limit of {} requests per minute, try again in {} seconds", self.key.name, self.requests_per_minute, 60 - crate::unix_time() % 60))
+                .with_lengths(self.requests_per_minute as usize, made));
+        }
+
+        window.requests += 1;
+        drop(window);
+        self.update_usage(|usage| usage.requests += 1);
+
+        Ok(())
+    }
+
+    // only counted in memory, save_usage writes it out when the rate window moves on and at shutdown
+    fn update_usage(&self, update: impl FnOnce(&mut KeyUsage))
+    {
+        update(&mut lock(&self.usage));
+        self.unsaved.store(true, Ordering::Relaxed);
+    }
+
+    // losing the accounting of a request should not cost the client its answer, so this only logs. the write happens
+    // outside of the usage lock so requests with the same key do not wait on the disk to count themselves
+    pub fn save_usage(&self)
+    {
+        let tree = match &self.usage_tree
+        {
+            Some(tree) => tree,
+            None => return
+        };
+
+        let _saving = lock(&self.saving);
+        if !self.unsaved.swap(false, Ordering::Relaxed)
+        {
+            return;
+        }
+        let usage = self.usage();
+
+        match serde_json::to_vec(&usage).map_err(|x| x.to_string()).and_then(|bytes| tree.insert(self.key.name.as_bytes(), bytes).map_err(|x| x.to_string()))
+        {
+            Ok(_) => (),
+            Err(error) =>
+            {
+                self.unsaved.store(true, Ordering::Relaxed);
+                tracing::warn!(key = %self.key.name, %error, "could not save the usage of the key")
+            }
+        }
+    }
+
+    pub fn usage(&self) -> KeyUsage
+    {
+        lock(&self.usage).clone()
+    }
+}
+
+pub struct ApiKeys
+{
+    enabled: bool,
+    config: AuthConfig,
+    // by the key itself, which is what a request carries
+    accounts: RwLock<HashMap<String, Arc<Account>>>,
+    keys_tree: Option<sled::Tree>,
+    usage_tree: Option<sled::Tree>
+}
+
+impl ApiKeys
+{
+    // keys from the file come first, a key in the store with the same name replaces the one from the file
+    pub fn load(config: &AuthConfig, store: Option<&Store>) -> Result<Self, String>
+    {
+        let keys_tree = store.map(|store| store.tree("api_keys")).transpose()?;
+        let usage_tree = store.map(|store| store.tree("api_usage")).transpose()?;
+
+        let mut keys: Vec<ApiKey> = Vec::new();
+
+        if !config.keys_file.is_empty()
+        {
+            let text = std::fs::read_to_string(&config.keys_file).map_err(|x| format!("Could not read the API keys from {}: {}", config.keys_file, x))?;
+            let file_keys: Vec<ApiKey> = parse::parse_json(&text).map_err(|x| format!("Could not read the API keys from {}: {}", config.keys_file, x))?;
+            keys.extend(file_keys);
+        }
+
+        if let Some(tree) = &keys_tree
+        {
+            for entry in tree.iter()
+            {
+                let (_, bytes) = entry.map_err(|x| format!("Could not read the API keys from the store: {}", x))?;
+                let key: ApiKey = serde_json::from_slice(&bytes).map_err(|x| format!("Could not read an API key from the store: {}", x))?;
+
+                keys.retain(|other| other.name != key.name);
+                keys.push(key);
+            }
+        }
+
+        let api_keys = ApiKeys
+        {
+            enabled: config.enabled,
+            config: config.clone(),
+            accounts: RwLock::new(HashMap::new()),
+            keys_tree: keys_tree,
+            usage_tree: usage_tree
+        };
+
+        for key in keys
+        {
+            api_keys.insert(key);
+        }
+
+        Ok(api_keys)
+    }
+
+    fn insert(&self, key: ApiKey)
+    {
+        let account = Arc::new(Account::new(key.clone(), &self.config, self.usage_tree.clone()));
+
+        let mut accounts = self.accounts.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        accounts.retain(|_, other| other.key.name != key.name);
+        accounts.insert(key.key, account);
+    }
+
+    fn account(&self, key: &str) -> Option<Arc<Account>>
+    {
+        self.accounts.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(key).cloned()
+    }
+
+    // keys added at runtime go into the store so they are still there after a restart
+    pub fn add(&self, key: ApiKey) -> Result<(), SimplexError>
+    {
+        if key.name.is_empty() || key.key.is_empty()
+        {
+            return Err(SimplexError::new(ErrorCode::InvalidKey, format!("An API key needs a name and a key")));
+        }
+
+        if self.accounts.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&key.key).map_or(false, |other| other.key.name != key.name)
+        {
+            return Err(SimplexError::new(ErrorCode::InvalidKey, format!("The key is already in use under another name")));
+        }
+
+        let tree = match &self.keys_tree
+        {
+            Some(tree) => tree,
+            None => return Err(SimplexError::new(ErrorCode::Storage, format!("There is no store to keep the API key in")))
+        };
+
+        let bytes = serde_json::to_vec(&key).map_err(|x| SimplexError::internal(x.to_string()))?;
+        tree.insert(key.name.as_bytes(), bytes).map_err(|x| SimplexError::new(ErrorCode::Storage, format!("Could not save the API key: {}", x)))?;
+
+        self.insert(key);
+        Ok(())
+    }
+
+    // only keys in the store can be removed, the ones from the keys file would be back after a restart
+    pub fn remove(&self, name: &str) -> Result<bool, SimplexError>
+    {
+        let removed = match &self.keys_tree
+        {
+            Some(tree) => tree.remove(name.as_bytes()).map_err(|x| SimplexError::new(ErrorCode::Storage, format!("Could not remove the API key: {}", x)))?.is_some(),
+            None => false
+        };
+
+        if removed
+        {
+            self.accounts.write().unwrap_or_else(|poisoned| poisoned.into_inner()).retain(|_, account| account.key.name != name);
+        }
+
+        Ok(removed)
+    }
+
+    // shared with the caller so the usage can be written from a blocking thread at shutdown
+    pub fn accounts(&self) -> Vec<Arc<Account>>
+    {
+        self.accounts.read().unwrap_or_else(|poisoned| poisoned.into_inner()).values().cloned().collect()
+    }
+
+    pub fn usage(&self) -> Vec<KeyUsage>
+    {
+        let mut usage: Vec<KeyUsage> = self.accounts.read().unwrap_or_else(|poisoned| poisoned.into_inner()).values().map(|account| account.usage()).collect();
+        usage.sort_by(|x, y| x.name.cmp(&y.name));
+        usage
+    }
+
+    // the caller a job was queued by before a restart, without counting a request against its rate limit. a key that
+    // was removed since leaves the job to nobody
+    pub fn caller_named(&self, name: Option<&str>) -> Caller
+    {
+        let accounts = self.accounts.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Caller(name.and_then(|name| accounts.values().find(|account| account.key.name == name).cloned()))
+    }
+
+    // checks the key and counts the request against its rate limit
+    pub fn authenticate(&self, key: Option<&str>) -> Result<Caller, SimplexError>
+    {
+        self.authenticate_at(key, crate::unix_time() / 60)
+    }
+
+    // minute is the number of minutes since the unix epoch, the rate limit window the request falls in
+    pub fn authenticate_at(&self, key: Option<&str>, minute: u64) -> Result<Caller, SimplexError>
+    {
+        if !self.enabled
+        {
+            return Ok(Caller(None));
+        }
+
+        let key = match key
+        {
+            Some(key) => key,
+            None => return Err(SimplexError::new(ErrorCode::Unauthorized, format!("An API key is needed, send it in the X-API-Key header")))
+        };
+
+        let account = match self.account(key)
+        {
+            Some(account) => account,
+            None => return Err(SimplexError::new(ErrorCode::Unauthorized, format!("The API key is not known")))
+        };
+
+        account.admit(minute)?;
+        Ok(Caller(Some(account)))
+    }
+}
+
+// who made the request, nobody when API keys are turned off
+#[derive(Clone)]
+pub struct Caller(Option<Arc<Account>>);
+
+impl Caller
+{
+    pub fn anonymous() -> Self
+    {
+        Caller(None)
+    }
+
+    // the name of the key, which is what outlives a restart
+    pub fn name(&self) -> Option<&str>
+    {
+        self.0.as_ref().map(|account| account.key.name.as_str())
+    }
+
+    // jobs and runs belong to the key that made them, the others are told they do not exist. without API keys
+    // everything belongs to nobody
+    pub fn owns(&self, owner: Option<&str>) -> bool
+    {
+        self.name() == owner
+    }
+
+    // the size limits of the key, checked before anything is solved
+    pub fn check(&self, linear_program: &LinearProgram) -> Result<(), SimplexError>
+    {
+        let account = match &self.0
+        {
+            Some(account) => account,
+            None => return Ok(())
+        };
+
+        let rows = linear_program.tableau.len();
+        let columns = linear_program.tableau.iter().map(|row| row.a_ij.len()).max().unwrap_or(0);
+
+        if rows > account.max_rows
+        {
+            return Err(SimplexError::new(ErrorCode::TooLarge, format!("The key {} allows at most {} rows but the problem has {}", account.key.name, account.max_rows, rows))
+                .with_field("tableau").with_limit("max_rows").with_lengths(account.max_rows, rows));
+        }
+
+        if columns > account.max_columns
+        {
+            return Err(SimplexError::new(ErrorCode::TooLarge, format!("The key {} allows at most {} columns but the problem has {}", account.key.name, account.max_columns, columns))
+                .with_field("tableau").with_limit("max_columns").with_lengths(account.max_columns, columns));
+        }
+
+        Ok(())
+    }
+
+    pub fn record(&self, response: &LinearProgramResponse, wall_time: Duration)
+    {
+        self.record_solves(1, iterations(response), wall_time);
+    }
+
+    pub fn record_solves(&self, solves: u64, iterations: u64, wall_time: Duration)
+    {
+        if let Some(account) = &self.0
+        {
+            account.update_usage(|usage|
+            {
+                usage.solves += solves;
+                usage.iterations += iterations;
+                usage.wall_time_us += wall_time.as_micros() as u64;
+            });
+        }
+    }
+
+    fn admin(&self) -> Result<(), SimplexError>
+    {
+        match &self.0
+        {
+            Some(account) if account.key.admin => Ok(()),
+            _ => Err(SimplexError::new(ErrorCode::Forbidden, format!("Managing API keys needs an admin key")))
+        }
+    }
+}
+
+pub fn iterations(response: &LinearProgramResponse) -> u64
+{
+    match response
+    {
+        LinearProgramResponse::LinearProgram(report) => report.statistics.iterations as u64,
+        LinearProgramResponse::LimitReached(report) => report.statistics.iterations as u64,
+        _ => 0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Caller
+{
+    type Error = SimplexError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, SimplexError>
+    {
+        // kept for the request so a request is only counted once however many guards ask for the caller
+        let outcome = request.local_cache(||
+        {
+            let api_keys = match request.rocket().state::<ApiKeys>()
+            {
+                Some(api_keys) => api_keys,
+                None => return Ok(Caller(None))
+            };
+
+            let headers = request.headers();
+            let key = headers.get_one("X-API-Key").or_else(|| headers.get_one("Authorization").and_then(|x| x.strip_prefix("Bearer ")));
+
+            api_keys.authenticate(key)
+        });
+
+        match outcome
+        {
+            Ok(caller) => Outcome::Success(caller.clone()),
+            Err(error) =>
+            {
+                error::remember_failure(request, error);
+                let status = if error.code == ErrorCode::RateLimited { Status::TooManyRequests } else { Status::Unauthorized };
+                Outcome::Error((status, error.clone()))
+            }
+        }
+    }
+}
+
+#[get("/usage")]
+fn key_usage(caller: Caller) -> ApiResult<Json<KeyUsage>>
+{
+    match &caller.0
+    {
+        Some(account) => Ok(Json(account.usage())),
+        None => Err(api_error(SimplexError::new(ErrorCode::NotFound, format!("API keys are not turned on"))))
+    }
+}
+
+#[get("/keys")]
+fn list_keys(caller: Caller, api_keys: &State<ApiKeys>) -> ApiResult<Json<Vec<KeyUsage>>>
+{
+    caller.admin().map_err(api_error)?;
+    Ok(Json(api_keys.usage()))
+}
+
+#[post("/keys", data = "<key>")]
+fn add_key(key: JsonBody<ApiKey>, caller: Caller, api_keys: &State<ApiKeys>) -> ApiResult<status::Created<Json<ApiKey>>>
+{
+    caller.admin().map_err(api_error)?;
+
+    let key = key.into_inner();
+    api_keys.add(key.clone()).map_err(api_error)?;
+
+    Ok(status::Created::new(format!("/keys/{}", key.name)).body(Json(key)))
+}
+
+#[delete("/keys/<name>")]
+fn remove_key(name: &str, caller: Caller, api_keys: &State<ApiKeys>) -> ApiResult<Option<Json<String>>>
+{
+    caller.admin().map_err(api_error)?;
+
+    match api_keys.remove(name).map_err(api_error)?
+    {
+        true => Ok(Some(Json(String::from(name)))),
+        false => Ok(None)
+    }
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![key_usage, list_keys, add_key, remove_key]
+}
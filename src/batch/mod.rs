@@ -0,0 +1,305 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use rocket::serde::{Deserialize, Deserializer, Serialize};
+use rocket::serde::de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use rocket::{Route, State};
+use schemars::JsonSchema;
+
+use crate::auth::Caller;
+use crate::config::{BatchConfig, SolverConfig};
+use crate::logging::RequestId;
+use crate::error::{ErrorCode, SimplexError};
+use crate::limits;
+use crate::parse::JsonBody;
+use crate::row_arithmetic::LinearProgram;
+use crate::{JsonStream, LimitsQuery, LinearProgramResponse, SolveOptions};
+
+// only the parts of a scenario that differ from the base problem
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ScenarioOverride
+{
+    #[serde(default, deserialize_with = "limits::deserialize_override_costs")]
+    pub costs: Option<Vec<f32>>,
+    #[serde(default, deserialize_with = "limits::deserialize_override_b")]
+    pub b: Option<Vec<f32>>
+}
+
+impl ScenarioOverride
+{
+    // overriding b also moves the starting solution since the identity basis has x_i = b_i
+    pub fn apply(&self, base: &LinearProgram) -> Result<LinearProgram, SimplexError>
+    {
+        if let Some(b) = &self.b
+        {
+            if b.len() != base.tableau.len()
+            {
+                return Err(SimplexError::new(ErrorCode::InvalidOverride, 
+                    format!("The override for b does not have one entry per row: entries = {}, rows = {}", b.len(), base.tableau.len()))
+                    .with_field("b").with_lengths(base.tableau.len(), b.len()));
+            }
+        }
+
+        let mut linear_program = base.clone();
+
+        if let Some(costs) = &self.costs
+        {
+            linear_program.costs = costs.clone();
+        }
+
+        if let Some(b) = &self.b
+        {
+            for (row_index, b_i) in b.iter().enumerate()
+            {
+                linear_program.tableau[row_index].b_i = *b_i;
+                if row_index < linear_program.solution.len()
+                {
+                    linear_program.solution[row_index] = *b_i;
+                }
+            }
+        }
+
+        Ok(linear_program)
+    }
+}
+
+// read by hand rather than derived as untagged, serde would buffer the whole body to try each variant before anything is
+// counted against the limits
+#[derive(Debug, JsonSchema)]
+#[serde(crate = "rocket::serde", untagged)]
+pub enum BatchRequest
+{
+    Problems(Vec<LinearProgram>),
+    Scenarios { base: LinearProgram, overrides: Vec<ScenarioOverride> }
+}
+
+impl<'de> Deserialize<'de> for BatchRequest
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        deserializer.deserialize_any(BatchVisitor)
+    }
+}
+
+struct BatchVisitor;
+
+impl<'de> Visitor<'de> for BatchVisitor
+{
+    type Value = BatchRequest;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a list of problems or a base problem with its overrides")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, sequence: A) -> Result<BatchRequest, A::Error>
+    {
+        limits::visit_problems(sequence).map(BatchRequest::Problems)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<BatchRequest, A::Error>
+    {
+        let mut base: Option<LinearProgram> = None;
+        let mut overrides: Option<Vec<ScenarioOverride>> = None;
+
+        while let Some(key) = map.next_key::<String>()?
+        {
+            match key.as_str()
+            {
+                "base" => base = Some(map.next_value()?),
+                "overrides" => overrides = Some(map.next_value::<Overrides>()?.0),
+                _ => { map.next_value::<IgnoredAny>()?; }
+            };
+        }
+
+        match (base, overrides)
+        {
+            (Some(base), Some(overrides)) => Ok(BatchRequest::Scenarios { base: base, overrides: overrides }),
+            (None, _) => Err(de::Error::missing_field("base")),
+            (_, None) => Err(de::Error::missing_field("overrides"))
+        }
+    }
+}
+
+// the overrides count against batch.max_problems like the problems of a plain list
+struct Overrides(Vec<ScenarioOverride>);
+
+impl<'de> Deserialize<'de> for Overrides
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        deserializer.deserialize_seq(OverridesVisitor)
+    }
+}
+
+struct OverridesVisitor;
+
+impl<'de> Visitor<'de> for OverridesVisitor
+{
+    type Value = Overrides;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a list of overrides")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, sequence: A) -> Result<Overrides, A::Error>
+    {
+        limits::visit_problems(sequence).map(Overrides)
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum BatchItemStatus
+{
+    Optimal,
+    Unbound,
+    LimitReached,
+    Error
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchItem
+{
+    pub index: usize,
+    pub status: BatchItemStatus,
+    pub response: LinearProgramResponse
+}
+
+impl BatchItem
+{
+    pub fn new(index: usize, response: LinearProgramResponse) -> Self
+    {
+        let status = match &response
+        {
+            LinearProgramResponse::LinearProgram(_) => BatchItemStatus::Optimal,
+            LinearProgramResponse::Unbound(_) => BatchItemStatus::Unbound,
+            LinearProgramResponse::LimitReached(_) => BatchItemStatus::LimitReached,
+            LinearProgramResponse::Error(_) => BatchItemStatus::Error
+        };
+
+        BatchItem { index: index, status: status, response: response }
+    }
+}
+
+impl BatchRequest
+{
+    pub fn len(&self) -> usize
+    {
+        match self
+        {
+            BatchRequest::Problems(problems) => problems.len(),
+            BatchRequest::Scenarios { overrides, .. } => overrides.len()
+        }
+    }
+
+    // a scenario is only built from the base problem when a thread gets to it, so a batch of overrides does not hold a
+    // copy of the base for each of them
+    pub fn problem(&self, index: usize) -> Option<Result<LinearProgram, SimplexError>>
+    {
+        match self
+        {
+            BatchRequest::Problems(problems) => problems.get(index).cloned().map(Ok),
+            BatchRequest::Scenarios { base, overrides } => overrides.get(index).map(|scenario| scenario.apply(base))
+        }
+    }
+}
+
+// every thread takes the next unsolved problem so one slow problem does not hold up a whole share of the batch. a problem
+// too large for the key fails on its own like a scenario that does not fit the base problem
+pub fn solve_batch(batch: BatchRequest, options: &SolveOptions, threads: usize, caller: &Caller) -> Vec<BatchItem>
+{
+    let next_problem = AtomicUsize::new(0);
+    let span = tracing::Span::current();
+    let threads = threads.max(1).min(batch.len().max(1));
+
+    let mut items: Vec<BatchItem> = thread::scope(|scope|
+    {
+        let handles: Vec<_> = (0..threads).map(|_| scope.spawn(|| 
+        {
+            let _entered = span.enter();
+            let mut solved: Vec<BatchItem> = Vec::new();
+
+            loop
+            {
+                let index = next_problem.fetch_add(1, Ordering::Relaxed);
+                let problem = match batch.problem(index)
+                {
+                    Some(problem) => problem.and_then(|linear_program| caller.check(&linear_program).map(|_| linear_program)),
+                    None => return solved
+                };
+
+                let response = match problem
+                {
+                    Ok(linear_program) =>
+                    {
+                        let (response, wall_time) = crate::timed_solve(linear_program, options);
+                        caller.record(&response, wall_time);
+                        response
+                    },
+                    Err(error) => LinearProgramResponse::Error(error)
+                };
+
+                solved.push(BatchItem::new(index, response));
+            }
+        })).collect();
+
+        handles.into_iter().filter_map(|handle| handle.join().ok()).flatten().collect()
+    });
+
+    items.sort_by_key(|item| item.index);
+
+    // a thread that panicked loses the problems it was working on
+    for index in 0..batch.len()
+    {
+        if items.get(index).map_or(true, |item| item.index != index)
+        {
+            items.insert(index, BatchItem::new(index, LinearProgramResponse::Error(SimplexError::internal(format!("The solver stopped unexpectedly")))));
+        }
+    }
+
+    items
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde", untagged)]
+enum BatchAnswer
+{
+    Items(Vec<BatchItem>),
+    Failed(LinearProgramResponse)
+}
+
+#[post("/batch?<limits..>", data = "<batch>")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0, problems = batch.len()))]
+async fn batch_solve(batch: JsonBody<BatchRequest>, limits: LimitsQuery, request_id: RequestId, caller: Caller, batch_config: &State<BatchConfig>,
+    solver_config: &State<SolverConfig>) -> JsonStream<'static>
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let options = limits.options(solver_config, Some(cancelled.clone()));
+
+    let threads = batch_config.threads();
+    let batch = batch.into_inner();
+
+    // the status is sent before the problems are solved, a solver that stopped unexpectedly can only show in the body
+    let span = tracing::Span::current();
+    let answer = async move
+    {
+        match rocket::tokio::task::spawn_blocking(move || span.in_scope(|| solve_batch(batch, &options, threads, &caller))).await
+        {
+            Ok(items) => BatchAnswer::Items(items),
+            Err(error) => BatchAnswer::Failed(LinearProgramResponse::Error(SimplexError::internal(format!("The solver stopped unexpectedly: {}", error))))
+        }
+    };
+
+    crate::stream_answer(cancelled, answer)
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![batch_solve]
+}
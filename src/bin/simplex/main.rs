@@ -0,0 +1,6 @@
+use std::process::ExitCode;
+
+fn main() -> ExitCode
+{
+    rust_backend::run_cli()
+}
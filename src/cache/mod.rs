@@ -0,0 +1,309 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rocket::serde::{Deserialize, Serialize};
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use schemars::JsonSchema;
+
+use crate::auth::Caller;
+use crate::config::CacheConfig;
+use crate::row_arithmetic::{LinearProgram, PivotRule};
+use crate::{LinearProgramResponse, SolveOptions};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// entries are looked up by the canonical bytes themselves so that two problems which happen to share a hash are never
+// mixed up, the hash is what the logs show
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey
+{
+    pub hash: u64,
+    pub canonical: Vec<u8>
+}
+
+impl CacheKey
+{
+    // the key order of the json and how the numbers were written do not matter once it is parsed, -0 and 0 are the same
+    // and every NaN is the same NaN. the rows are kept in order since the first m columns belong to them, and the
+    // relative costs are left out because the solver works them out from the costs
+    pub fn new(linear_program: &LinearProgram, options: &SolveOptions) -> Self
+    {
+        let mut canonical: Vec<u8> = Vec::new();
+
+        canonical.push(match options.pivot_rule
+        {
+            PivotRule::Lexicographic => 0,
+            PivotRule::Bland => 1,
+            PivotRule::Dantzig => 2
+        });
+        canonical.push(options.alternatives as u8);
+        canonical.push(options.vertices as u8);
+
+        push_length(&mut canonical, linear_program.tableau.len());
+        for row in linear_program.tableau.iter()
+        {
+            push_numbers(&mut canonical, &row.a_ij);
+            push_number(&mut canonical, row.b_i);
+        }
+
+        push_numbers(&mut canonical, &linear_program.costs);
+        push_length(&mut canonical, linear_program.relative_costs.a_ij.len());
+        push_numbers(&mut canonical, &linear_program.solution);
+
+        CacheKey { hash: fnv_1a(&canonical), canonical: canonical }
+    }
+}
+
+fn push_length(canonical: &mut Vec<u8>, length: usize)
+{
+    canonical.extend_from_slice(&(length as u64).to_le_bytes());
+}
+
+fn push_numbers(canonical: &mut Vec<u8>, numbers: &Vec<f32>)
+{
+    push_length(canonical, numbers.len());
+    for number in numbers.iter()
+    {
+        push_number(canonical, *number);
+    }
+}
+
+fn push_number(canonical: &mut Vec<u8>, number: f32)
+{
+    let number = if number.is_nan() { f32::NAN } else if number == 0.0 { 0.0 } else { number };
+    canonical.extend_from_slice(&number.to_bits().to_le_bytes());
+}
+
+pub fn fnv_1a(bytes: &[u8]) -> u64
+{
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct PersistedEntry
+{
+    canonical: Vec<u8>,
+    response: LinearProgramResponse
+}
+
+struct Entry
+{
+    response: LinearProgramResponse,
+    last_used: u64
+}
+
+// the entries by their canonical bytes, and the same entries by when they were last used so the least recent one is
+// the first in the order
+struct Entries
+{
+    map: HashMap<Vec<u8>, Entry>,
+    order: BTreeMap<u64, Vec<u8>>,
+    clock: u64
+}
+
+impl Entries
+{
+    fn touch(&mut self, canonical: &[u8]) -> Option<&Entry>
+    {
+        self.clock += 1;
+        let clock = self.clock;
+
+        let entry = self.map.get_mut(canonical)?;
+        let canonical = self.order.remove(&entry.last_used)?;
+        entry.last_used = clock;
+        self.order.insert(clock, canonical);
+
+        Some(entry)
+    }
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct CacheStatistics
+{
+    pub capacity: usize,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub persisted: bool
+}
+
+// least recently used responses are evicted first, with a tree the entries also survive a restart
+pub struct ResultCache
+{
+    entries: Mutex<Entries>,
+    tree: Option<sled::Tree>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64
+}
+
+impl ResultCache
+{
+    pub fn new(config: &CacheConfig, tree: Option<sled::Tree>) -> Self
+    {
+        let cache = ResultCache
+        {
+            entries: Mutex::new(Entries { map: HashMap::new(), order: BTreeMap::new(), clock: 0 }),
+            tree: tree,
+            capacity: config.capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0)
+        };
+
+        cache.load();
+        cache
+    }
+
+    fn load(&self)
+    {
+        let tree = match &self.tree
+        {
+            Some(tree) => tree,
+            None => return
+        };
+
+        for entry in tree.iter()
+        {
+            let (_, bytes) = match entry
+            {
+                Ok(entry) => entry,
+                Err(_) => continue
+            };
+
+            let persisted: PersistedEntry = match serde_json::from_slice(&bytes)
+            {
+                Ok(persisted) => persisted,
+                // an entry written by an older version is only a missed hit
+                Err(_) => continue
+            };
+
+            let key = CacheKey { hash: fnv_1a(&persisted.canonical), canonical: persisted.canonical };
+            self.insert_entry(key, persisted.response, false);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries>
+    {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<LinearProgramResponse>
+    {
+        match self.lock().touch(&key.canonical)
+        {
+            Some(entry) =>
+            {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.response.clone())
+            },
+            None =>
+            {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    // a solve that hit a limit could finish next time with looser limits, so those are never kept
+    pub fn insert(&self, key: CacheKey, response: &LinearProgramResponse)
+    {
+        match response
+        {
+            LinearProgramResponse::LimitReached(_) => (),
+            response => self.insert_entry(key, response.clone(), true)
+        }
+    }
+
+    fn insert_entry(&self, key: CacheKey, response: LinearProgramResponse, persist: bool)
+    {
+        if self.capacity == 0
+        {
+            return;
+        }
+
+        let mut entries = self.lock();
+
+        if let Some(entry) = entries.map.remove(&key.canonical)
+        {
+            entries.order.remove(&entry.last_used);
+        }
+
+        while entries.map.len() >= self.capacity
+        {
+            let oldest = match entries.order.pop_first()
+            {
+                Some((_, canonical)) => canonical,
+                None => break
+            };
+
+            entries.map.remove(&oldest);
+            self.forget(&oldest);
+        }
+
+        if persist
+        {
+            self.persist(&key, &response);
+        }
+
+        entries.clock += 1;
+        let clock = entries.clock;
+        entries.order.insert(clock, key.canonical.clone());
+        entries.map.insert(key.canonical, Entry { response: response, last_used: clock });
+    }
+
+    // the cache still answers from memory when the disk fails, so these only log
+    fn persist(&self, key: &CacheKey, response: &LinearProgramResponse)
+    {
+        let tree = match &self.tree
+        {
+            Some(tree) => tree,
+            None => return
+        };
+
+        let persisted = PersistedEntry { canonical: key.canonical.clone(), response: response.clone() };
+        match serde_json::to_vec(&persisted).map_err(|x| x.to_string()).and_then(|bytes| tree.insert(key.canonical.as_slice(), bytes).map_err(|x| x.to_string()))
+        {
+            Ok(_) => (),
+            Err(error) => tracing::warn!(hash = %format!("{:x}", key.hash), %error, "could not persist the cached result")
+        }
+    }
+
+    fn forget(&self, stored_key: &[u8])
+    {
+        if let Some(tree) = &self.tree
+        {
+            if let Err(error) = tree.remove(stored_key)
+            {
+                tracing::warn!(hash = %format!("{:x}", fnv_1a(stored_key)), %error, "could not remove the cached result");
+            }
+        }
+    }
+
+    pub fn statistics(&self) -> CacheStatistics
+    {
+        CacheStatistics
+        {
+            capacity: self.capacity,
+            entries: self.lock().map.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            persisted: self.tree.is_some()
+        }
+    }
+}
+
+#[get("/cache")]
+fn cache_statistics(_caller: Caller, cache: &State<ResultCache>) -> Json<CacheStatistics>
+{
+    Json(cache.statistics())
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![cache_statistics]
+}
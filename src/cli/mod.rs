@@ -0,0 +1,522 @@
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Read, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::json;
+
+use crate::error::SimplexError;
+use crate::model::{self, Model, StandardForm};
+use crate::repl::Session;
+use crate::row_arithmetic::{self, IterationEvent, LinearProgram, PivotRule, SolveLimits};
+use crate::{LinearProgramResponse, SolveOptions};
+use crate::{lp_file, mps, parse, render};
+
+// what the simplex binary exits with, so a script can tell the outcomes apart without reading the output. 2 is left to
+// clap, which exits with it on arguments it cannot read
+pub const EXIT_OPTIMAL: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_UNBOUNDED: i32 = 3;
+pub const EXIT_LIMIT_REACHED: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat
+{
+    // the LinearProgram the server takes
+    Json,
+    // free MPS
+    Mps,
+    // the CPLEX LP format
+    Lp
+}
+
+impl InputFormat
+{
+    pub fn parse(name: &str) -> Result<Self, String>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "json" => Ok(InputFormat::Json),
+            "mps" => Ok(InputFormat::Mps),
+            "lp" => Ok(InputFormat::Lp),
+            _ => Err(format!("{} is not a supported format, use json, mps or lp", name))
+        }
+    }
+
+    // the format a file is in by its extension, json when it has none of the known ones
+    pub fn of_path(path: Option<&str>) -> Self
+    {
+        let extension = path.and_then(|path| std::path::Path::new(path).extension()).map(|x| x.to_string_lossy().to_lowercase());
+
+        match extension.as_deref()
+        {
+            Some("mps") => InputFormat::Mps,
+            Some("lp") => InputFormat::Lp,
+            _ => InputFormat::Json
+        }
+    }
+}
+
+// a json problem is in standard form already, mps and lp files hold a model that still has to be brought into it
+#[derive(Debug, Clone)]
+pub enum Problem
+{
+    LinearProgram(LinearProgram),
+    Model(Model)
+}
+
+impl Problem
+{
+    pub fn standard_form(self, big_m: Option<f64>) -> Result<StandardForm, String>
+    {
+        match self
+        {
+            Problem::LinearProgram(linear_program) => Ok(StandardForm::of(linear_program)),
+            Problem::Model(model) => model::to_standard_form(&model, big_m)
+        }
+    }
+
+    pub fn model(&self) -> Model
+    {
+        match self
+        {
+            Problem::LinearProgram(linear_program) => model::from_linear_program(linear_program, None),
+            Problem::Model(model) => model.clone()
+        }
+    }
+}
+
+pub fn parse_pivot_rule(name: &str) -> Result<PivotRule, String>
+{
+    match name.to_lowercase().as_str()
+    {
+        "lexicographic" => Ok(PivotRule::Lexicographic),
+        "bland" => Ok(PivotRule::Bland),
+        "dantzig" => Ok(PivotRule::Dantzig),
+        _ => Err(format!("{} is not a pivot rule, use lexicographic, bland or dantzig", name))
+    }
+}
+
+// no path or - reads stdin
+pub fn read_input(path: Option<&str>) -> Result<String, String>
+{
+    match path
+    {
+        None | Some("-") =>
+        {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text).map_err(|x| format!("Could not read stdin: {}", x))?;
+            Ok(text)
+        },
+        Some(path) => std::fs::read_to_string(path).map_err(|x| format!("Could not read {}: {}", path, x))
+    }
+}
+
+pub fn read_problem(path: Option<&str>, format: Option<InputFormat>) -> Result<Problem, String>
+{
+    let text = read_input(path)?;
+    let source = path.filter(|path| *path != "-").unwrap_or("stdin");
+
+    match format.unwrap_or(InputFormat::of_path(path))
+    {
+        InputFormat::Json => parse::parse_json(&text).map(Problem::LinearProgram).map_err(|error| describe(source, &error)),
+        InputFormat::Mps => mps::read(&text).map(Problem::Model).map_err(|message| format!("{}:{}", source, message.trim_start_matches("line "))),
+        InputFormat::Lp => lp_file::read(&text).map(Problem::Model).map_err(|message| format!("{}:{}", source, message.trim_start_matches("line ")))
+    }
+}
+
+// json is always written in standard form, the only form the server takes
+pub fn write_problem(standard_form: &StandardForm, model: &Model, format: InputFormat) -> String
+{
+    match format
+    {
+        InputFormat::Json => serde_json::to_string_pretty(&standard_form.linear_program).unwrap_or_default() + "\n",
+        InputFormat::Mps => mps::write(model),
+        InputFormat::Lp => lp_file::write(model)
+    }
+}
+
+// one line, with where in the file it went wrong when that is known
+pub fn describe(source: &str, error: &SimplexError) -> String
+{
+    match &error.details.json
+    {
+        Some(json) => format!("{}:{}:{}: {}", source, json.line, json.column, error.message),
+        None => format!("{}: {}", source, error.message)
+    }
+}
+
+pub fn exit_code(response: &LinearProgramResponse) -> i32
+{
+    match response
+    {
+        LinearProgramResponse::LinearProgram(_) => EXIT_OPTIMAL,
+        LinearProgramResponse::Unbound(_) => EXIT_UNBOUNDED,
+        LinearProgramResponse::LimitReached(_) => EXIT_LIMIT_REACHED,
+        LinearProgramResponse::Error(_) => EXIT_FAILED
+    }
+}
+
+fn write_vector(text: &mut String, standard_form: &StandardForm, solution: &Vec<f32>)
+{
+    // back to f32 so the values print as short as the solver computed them
+    let values: Vec<(String, f32)> = standard_form.values(solution).into_iter().map(|(name, value)| (name, value as f32)).collect();
+    let name_width = values.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(5);
+    let width = values.iter().map(|(_, value)| value.to_string().len()).max().unwrap_or(0);
+
+    for (name, value) in values.iter()
+    {
+        let _ = writeln!(text, "  {:<name_width$} {:>width$}", name, value, name_width = name_width, width = width);
+    }
+}
+
+// the response the way a person reads it, in the variables and the objective of the model it was read from. the json
+// output is the response as the server sends it
+pub fn format_table(response: &LinearProgramResponse, standard_form: &StandardForm) -> String
+{
+    let mut text = String::new();
+
+    match response
+    {
+        LinearProgramResponse::LinearProgram(report) =>
+        {
+            let _ = writeln!(text, "status      optimal\nobjective   {}\niterations  {} ({} degenerate, pivot rule {:?})",
+                standard_form.objective(&report.row.a_ij) as f32, report.statistics.iterations, report.statistics.degenerate_pivots, report.statistics.pivot_rule);
+            if report.statistics.cycling_detected
+            {
+                let _ = writeln!(text, "cycling was detected, the solve finished with Bland's rule");
+            }
+            if standard_form.artificial_in_use(&report.row.a_ij)
+            {
+                let _ = writeln!(text, "an artificial column is still in use, the model is infeasible or --big-m is too small");
+            }
+            if report.multiple_optima
+            {
+                let _ = writeln!(text, "the optimum is not unique");
+            }
+
+            let _ = writeln!(text, "\nsolution");
+            write_vector(&mut text, standard_form, &report.row.a_ij);
+
+            for (index, alternative) in report.alternative_optima.iter().flatten().enumerate()
+            {
+                let _ = writeln!(text, "\nalternative optimum {}", index + 1);
+                write_vector(&mut text, standard_form, alternative);
+            }
+
+            for (index, vertex) in report.vertices.iter().flatten().enumerate()
+            {
+                let _ = writeln!(text, "\nvertex {}", index + 1);
+                write_vector(&mut text, standard_form, vertex);
+            }
+        },
+        LinearProgramResponse::Unbound(message) =>
+        {
+            let _ = writeln!(text, "status      unbounded\n{}", message);
+        },
+        LinearProgramResponse::LimitReached(report) =>
+        {
+            let _ = writeln!(text, "status      limit reached ({:?})\nobjective   {} at the last basis reached\niterations  {}", report.reason, standard_form.objective(&report.row.a_ij) as f32, report.statistics.iterations);
+            let _ = writeln!(text, "\nsolution");
+            write_vector(&mut text, standard_form, &report.row.a_ij);
+        },
+        LinearProgramResponse::Error(error) =>
+        {
+            let _ = writeln!(text, "status      error ({:?})\n{}", error.code, error.message);
+        }
+    };
+
+    text
+}
+
+// solves models from files or stdin the same way the server does, without starting it
+#[derive(Parser)]
+#[command(name = "simplex", version, about = "Solve linear programs with the simplex method")]
+struct Arguments
+{
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command
+{
+    /// Solve a linear program, exits with 0 when optimal, 3 when unbounded, 4 when a limit was reached and 1 on errors
+    Solve(SolveArguments),
+    /// Convert a model between json, mps and lp, json is always written in the standard form the server takes
+    Convert(ConvertArguments),
+    /// Build tableaus and pivot them one command at a time, help lists the commands
+    Repl(ReplArguments)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output
+{
+    Table,
+    Json
+}
+
+#[derive(clap::Args)]
+struct SolveArguments
+{
+    /// The problem, stdin when left out or -
+    file: Option<String>,
+    /// Format of the problem, json, mps or lp, by the file extension when left out
+    #[arg(long, value_parser = InputFormat::parse)]
+    format: Option<InputFormat>,
+    /// Cost of the artificial columns mps and lp models get for >= and = rows, 1000 times the largest cost by default
+    #[arg(long)]
+    big_m: Option<f64>,
+    /// How the result is printed
+    #[arg(long, value_enum, default_value = "table")]
+    output: Output,
+    /// lexicographic, bland or dantzig
+    #[arg(long, value_parser = parse_pivot_rule, default_value = "lexicographic")]
+    pivot_rule: PivotRule,
+    /// Print every pivot, with the tableau after it in the table output
+    #[arg(long)]
+    trace: bool,
+    /// Stop after this many pivots
+    #[arg(long)]
+    max_iterations: Option<usize>,
+    /// Stop after this many milliseconds
+    #[arg(long)]
+    time_limit_ms: Option<u64>,
+    /// Also list other optimal vertices
+    #[arg(long)]
+    alternatives: bool,
+    /// Also list every vertex of the feasible region
+    #[arg(long)]
+    vertices: bool
+}
+
+#[derive(clap::Args)]
+struct ConvertArguments
+{
+    /// The model, stdin when left out or -
+    input: Option<String>,
+    /// Where the converted model goes, stdout when left out or -
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Format of the input, by the file extension when left out
+    #[arg(long, value_parser = InputFormat::parse)]
+    from: Option<InputFormat>,
+    /// Format of the output, by the file extension when left out
+    #[arg(long, value_parser = InputFormat::parse)]
+    to: Option<InputFormat>,
+    /// Write mps and lp in standard form too, with the slack, surplus and artificial columns the solver starts from
+    #[arg(long)]
+    standard_form: bool,
+    /// Cost of the artificial columns, 1000 times the largest cost by default
+    #[arg(long)]
+    big_m: Option<f64>
+}
+
+#[derive(clap::Args)]
+struct ReplArguments
+{
+    /// A problem to load first
+    file: Option<String>
+}
+
+// the simplex binary, parses its arguments and runs the command
+pub fn run() -> ExitCode
+{
+    let arguments = Arguments::parse();
+
+    let code = match arguments.command
+    {
+        Command::Solve(solve_arguments) => solve(solve_arguments),
+        Command::Convert(convert_arguments) => convert(convert_arguments),
+        Command::Repl(repl_arguments) => run_repl(repl_arguments)
+    };
+
+    ExitCode::from(code as u8)
+}
+
+fn solve(arguments: SolveArguments) -> i32
+{
+    let standard_form = match read_problem(arguments.file.as_deref(), arguments.format).and_then(|problem| problem.standard_form(arguments.big_m))
+    {
+        Ok(standard_form) => standard_form,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            return EXIT_FAILED;
+        }
+    };
+
+    let options = SolveOptions
+    {
+        alternatives: arguments.alternatives,
+        vertices: arguments.vertices,
+        pivot_rule: arguments.pivot_rule,
+        limits: SolveLimits { max_iterations: arguments.max_iterations, time_limit: arguments.time_limit_ms.map(Duration::from_millis), ..SolveLimits::default() }
+    };
+
+    let table = matches!(arguments.output, Output::Table);
+    if arguments.trace && table
+    {
+        print_start(&standard_form.linear_program);
+    }
+
+    let mut trace: Vec<IterationEvent> = Vec::new();
+    let response = crate::run_solver(standard_form.linear_program.clone(), &options, &mut |event, linear_program|
+    {
+        if !arguments.trace
+        {
+            return;
+        }
+
+        if table
+        {
+            println!("iteration {}: x{} enters, {} leaves, objective {}{}", event.iteration, event.entering + 1,
+                event.leaving.map_or(String::from("nothing"), |x| format!("x{}", x + 1)), event.objective, if event.degenerate { ", degenerate" } else { "" });
+            println!("{}", render::text_table(&linear_program.tableau, &linear_program.relative_costs, &linear_program.basis(), Some(&event.pivot)));
+        }
+        else
+        {
+            trace.push(event.clone());
+        }
+    });
+
+    match arguments.output
+    {
+        Output::Table => print!("{}", format_table(&response, &standard_form)),
+        Output::Json if arguments.trace => println!("{}", json!({ "trace": trace, "result": response })),
+        Output::Json => println!("{}", json!(response))
+    };
+
+    if let LinearProgramResponse::Error(error) = &response
+    {
+        if !table
+        {
+            eprintln!("{}", error.message);
+        }
+    }
+
+    exit_code(&response)
+}
+
+fn convert(arguments: ConvertArguments) -> i32
+{
+    let from = arguments.from.unwrap_or(InputFormat::of_path(arguments.input.as_deref()));
+    let to = arguments.to.unwrap_or(InputFormat::of_path(arguments.output.as_deref()));
+
+    let problem = match read_problem(arguments.input.as_deref(), Some(from))
+    {
+        Ok(problem) => problem,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            return EXIT_FAILED;
+        }
+    };
+
+    let model = problem.model();
+    let standard_form = match problem.standard_form(arguments.big_m)
+    {
+        Ok(standard_form) => standard_form,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            return EXIT_FAILED;
+        }
+    };
+
+    let model = if arguments.standard_form { model::from_linear_program(&standard_form.linear_program, Some(&standard_form.columns)) } else { model };
+    // standard form maximizes, what it changed about the objective has to be undone on the objective the solver reports
+    let standard = to == InputFormat::Json || arguments.standard_form;
+    if standard && standard_form.sense == model::Sense::Minimize
+    {
+        eprintln!("note: the model is minimized, standard form maximizes the negated objective");
+    }
+    if standard && standard_form.offset != 0.0
+    {
+        eprintln!("note: add {} to the objective the solver reports, it was moved out by shifting bounds and pricing out artificial columns", standard_form.offset);
+    }
+
+    let text = write_problem(&standard_form, &model, to);
+    let written = match arguments.output.as_deref()
+    {
+        None | Some("-") => std::io::stdout().write_all(text.as_bytes()).map_err(|x| format!("Could not write stdout: {}", x)),
+        Some(path) => std::fs::write(path, text).map_err(|x| format!("Could not write {}: {}", path, x))
+    };
+
+    match written
+    {
+        Ok(_) => EXIT_OPTIMAL,
+        Err(message) =>
+        {
+            eprintln!("{}", message);
+            EXIT_FAILED
+        }
+    }
+}
+
+// reads commands from stdin until quit or the end of it, the prompt is only printed to a terminal so a script of
+// commands can be piped in
+fn run_repl(arguments: ReplArguments) -> i32
+{
+    let mut session = Session::new();
+    let interactive = std::io::stdin().is_terminal();
+    let mut failed = false;
+
+    let mut commands: Vec<String> = arguments.file.iter().map(|file| format!("load {}", file)).collect();
+    if interactive
+    {
+        println!("simplex repl, help lists the commands");
+    }
+
+    let mut lines = std::io::stdin().lines();
+    loop
+    {
+        let command = match commands.pop()
+        {
+            Some(command) => command,
+            None =>
+            {
+                if interactive
+                {
+                    print!("simplex> ");
+                    let _ = std::io::stdout().flush();
+                }
+
+                match lines.next()
+                {
+                    Some(Ok(line)) => line,
+                    _ => break
+                }
+            }
+        };
+
+        match session.execute(&command)
+        {
+            Ok(Some(text)) if text.is_empty() => (),
+            Ok(Some(text)) => println!("{}", text.trim_end()),
+            Ok(None) => break,
+            Err(message) =>
+            {
+                eprintln!("{}", message);
+                failed = true;
+            }
+        };
+    }
+
+    // a piped script fails when one of its commands did
+    if failed && !interactive { EXIT_FAILED } else { EXIT_OPTIMAL }
+}
+
+// the starting tableau, only when it passes the checks the solver runs first
+fn print_start(linear_program: &LinearProgram)
+{
+    if row_arithmetic::perform_checks(linear_program).is_err()
+    {
+        return;
+    }
+
+    let mut start = linear_program.clone();
+    start.relative_costs = start.calculate_costs();
+    println!("start\n{}", render::text_table(&start.tableau, &start.relative_costs, &start.basis(), None));
+}
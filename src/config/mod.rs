@@ -0,0 +1,213 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use rocket::serde::Deserialize;
+
+use crate::row_arithmetic::SolveLimits;
+
+// read from the [global.solver] table in rocket.toml, ROCKET_SOLVER={max_iterations=...} works as well
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct SolverConfig
+{
+    pub max_iterations: usize,
+    pub time_limit_ms: u64
+}
+
+impl Default for SolverConfig
+{
+    fn default() -> Self
+    {
+        SolverConfig { max_iterations: 10_000, time_limit_ms: 10_000 }
+    }
+}
+
+impl SolverConfig
+{
+    // a request can ask for tighter limits than the server's but never looser ones
+    pub fn limits(&self, max_iterations: Option<usize>, time_limit_ms: Option<u64>, cancelled: Option<Arc<AtomicBool>>) -> SolveLimits
+    {
+        SolveLimits
+        {
+            max_iterations: Some(max_iterations.map_or(self.max_iterations, |x| x.min(self.max_iterations))),
+            time_limit: Some(Duration::from_millis(time_limit_ms.map_or(self.time_limit_ms, |x| x.min(self.time_limit_ms)))),
+            cancelled: cancelled,
+            shutdown: None
+        }
+    }
+}
+
+// read from the [global.jobs] table in rocket.toml
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct JobsConfig
+{
+    pub workers: usize,
+    pub queue_capacity: usize,
+    // finished jobs kept around for polling, the oldest ones are forgotten first
+    pub retained_jobs: usize,
+    // jobs still queued when the server shuts down are kept in the store and queued again on the next start
+    pub persist: bool
+}
+
+impl Default for JobsConfig
+{
+    fn default() -> Self
+    {
+        JobsConfig { workers: 2, queue_capacity: 64, retained_jobs: 1000, persist: true }
+    }
+}
+
+// read from the [global.batch] table in rocket.toml, threads = 0 uses every core
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct BatchConfig
+{
+    pub max_problems: usize,
+    pub threads: usize
+}
+
+impl Default for BatchConfig
+{
+    fn default() -> Self
+    {
+        BatchConfig { max_problems: 1000, threads: 0 }
+    }
+}
+
+impl BatchConfig
+{
+    pub fn threads(&self) -> usize
+    {
+        match self.threads
+        {
+            0 => std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1),
+            threads => threads
+        }
+    }
+}
+
+// read from the [global.store] table in rocket.toml, save_solves also keeps every solve made through /. it is off by
+// default since nothing removes those runs again
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct StoreConfig
+{
+    pub path: String,
+    pub save_solves: bool
+}
+
+impl Default for StoreConfig
+{
+    fn default() -> Self
+    {
+        StoreConfig { path: String::from("simplex_store"), save_solves: false }
+    }
+}
+
+// read from the [global.cache] table in rocket.toml, capacity = 0 turns the cache off and persist keeps it in the store
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct CacheConfig
+{
+    pub capacity: usize,
+    pub persist: bool
+}
+
+impl Default for CacheConfig
+{
+    fn default() -> Self
+    {
+        CacheConfig { capacity: 1024, persist: false }
+    }
+}
+
+// read from the [global.cors] table in rocket.toml, "*" in allowed_origins or allowed_headers allows any and cannot be
+// combined with allow_credentials
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct CorsConfig
+{
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    // how long a browser may keep the answer to a preflight, in seconds
+    pub max_age: u64,
+    pub allow_credentials: bool
+}
+
+impl Default for CorsConfig
+{
+    fn default() -> Self
+    {
+        CorsConfig
+        {
+            allowed_origins: vec![String::from("*")],
+            allowed_methods: ["GET", "POST", "DELETE", "OPTIONS"].iter().map(|x| String::from(*x)).collect(),
+            // the API key is sent in either of the last two
+            allowed_headers: ["Content-Type", "Accept", "X-API-Key", "Authorization"].iter().map(|x| String::from(*x)).collect(),
+            max_age: 86400,
+            allow_credentials: false
+        }
+    }
+}
+
+// read from the [global.auth] table in rocket.toml, the limits apply to every key that does not set its own
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct AuthConfig
+{
+    pub enabled: bool,
+    // a json list of keys, empty when the keys are only kept in the store
+    pub keys_file: String,
+    pub requests_per_minute: u32,
+    pub max_rows: usize,
+    pub max_columns: usize
+}
+
+impl Default for AuthConfig
+{
+    fn default() -> Self
+    {
+        AuthConfig { enabled: false, keys_file: String::new(), requests_per_minute: 60, max_rows: 1000, max_columns: 1000 }
+    }
+}
+
+// read from the [global.problem_limits] table in rocket.toml, the size of the body itself is limits.json
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct ProblemLimitsConfig
+{
+    pub max_rows: usize,
+    pub max_columns: usize,
+    // counted over the a_ij of every row, relative_costs included
+    pub max_nonzeros: usize
+}
+
+impl Default for ProblemLimitsConfig
+{
+    fn default() -> Self
+    {
+        ProblemLimitsConfig { max_rows: 10_000, max_columns: 10_000, max_nonzeros: 1_000_000 }
+    }
+}
+
+// read from the [global.logging] table in rocket.toml, level takes the same directives as RUST_LOG, e.g.
+// "info,rust_backend=debug" logs every pivot of the solver
+#[derive(Debug, Deserialize, Clone)]
+#[serde(crate = "rocket::serde", default)]
+pub struct LoggingConfig
+{
+    // text or json
+    pub format: String,
+    pub level: String
+}
+
+impl Default for LoggingConfig
+{
+    fn default() -> Self
+    {
+        LoggingConfig { format: String::from("text"), level: String::from("info") }
+    }
+}
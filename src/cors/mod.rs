@@ -0,0 +1,107 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::{Request, Response, Route};
+
+use crate::config::CorsConfig;
+
+pub struct Cors
+{
+    config: CorsConfig
+}
+
+impl Cors
+{
+    // browsers do not honour a "*" next to credentials, and echoing every origin back instead would let any site make
+    // requests with the user's credentials, so the two together are turned away before the server starts
+    pub fn new(config: CorsConfig) -> Result<Self, String>
+    {
+        if config.allow_credentials && config.allowed_origins.iter().any(|allowed| allowed == "*")
+        {
+            return Err(String::from("cors.allowed_origins cannot be \"*\" when cors.allow_credentials is set, list the origins instead"));
+        }
+
+        if config.allow_credentials && config.allowed_headers.iter().any(|allowed| allowed == "*")
+        {
+            return Err(String::from("cors.allowed_headers cannot be \"*\" when cors.allow_credentials is set, list the headers instead"));
+        }
+
+        Ok(Cors { config: config })
+    }
+
+    // the value for Access-Control-Allow-Origin, or None when the origin is not allowed
+    pub fn allowed_origin(&self, origin: &str) -> Option<String>
+    {
+        let origin = origin.trim_end_matches('/');
+
+        if self.config.allowed_origins.iter().any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+        {
+            return Some(String::from(origin));
+        }
+
+        match self.config.allowed_origins.iter().any(|allowed| allowed == "*")
+        {
+            true => Some(String::from("*")),
+            false => None
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors
+{
+    fn info(&self) -> Info
+    {
+        Info { name: "Add CORS headers to responses", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>)
+    {
+        // requests that are not cross-origin do not need any of this
+        let origin = match request.headers().get_one("Origin")
+        {
+            Some(origin) => origin,
+            None => return
+        };
+
+        let allowed_origin = match self.allowed_origin(origin)
+        {
+            Some(allowed_origin) => allowed_origin,
+            None => return
+        };
+
+        if allowed_origin != "*"
+        {
+            response.adjoin_header(Header::new("Vary", "Origin"));
+        }
+        response.set_header(Header::new("Access-Control-Allow-Origin", allowed_origin));
+
+        if self.config.allow_credentials
+        {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+
+        let preflight = request.method() == Method::Options && request.headers().contains("Access-Control-Request-Method");
+        if preflight
+        {
+            response.set_header(Header::new("Access-Control-Allow-Methods", self.config.allowed_methods.join(", ")));
+            response.set_header(Header::new("Access-Control-Allow-Headers", self.config.allowed_headers.join(", ")));
+            response.set_header(Header::new("Access-Control-Max-Age", self.config.max_age.to_string()));
+        }
+        else
+        {
+            response.set_header(Header::new("Access-Control-Expose-Headers", "X-Cache, X-Request-Id"));
+        }
+    }
+}
+
+// answers the preflight of every route, the headers themselves come from the fairing
+#[options("/<_..>")]
+fn preflight() -> Status
+{
+    Status::NoContent
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![preflight]
+}
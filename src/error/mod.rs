@@ -0,0 +1,171 @@
+use std::fmt::Display;
+
+use rocket::Request;
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+// the code is what clients should match on, the message is only for people and may change
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum ErrorCode
+{
+    // the request body
+    InvalidJson,
+    TooLarge,
+    InvalidOverride,
+    NotAcceptable,
+    UnsupportedDimension,
+    // the checks on a linear program
+    EmptyTableau,
+    RowLengthMismatch,
+    TooManyRows,
+    MissingIdentity,
+    InfeasibleSolution,
+    SolutionNotBasic,
+    SolutionMismatch,
+    NonFiniteValue,
+    ZeroRow,
+    DuplicateRow,
+    RankDeficient,
+    // row arithmetic while solving
+    RowOutOfRange,
+    ColumnOutOfRange,
+    ZeroPivot,
+    NoPositiveEntry,
+    LinearlyDependentRows,
+    AmbiguousBasis,
+    // the caller
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    InvalidKey,
+    // the server
+    NotFound,
+    QueueFull,
+    ShuttingDown,
+    Storage,
+    Internal
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", default)]
+pub struct ErrorDetails
+{
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    // the part of the linear program the error is about when it is not the tableau, e.g. costs or solution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub found: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f32>,
+    // the configured limit that was exceeded, e.g. max_rows or body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<JsonDiagnostic>
+}
+
+// where and why a request body could not be read as json, line and column count from 1 like serde_json does
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", default)]
+pub struct JsonDiagnostic
+{
+    // syntax, data or eof
+    pub category: String,
+    // e.g. tableau[1].a_ij[2], empty when the failure is not inside a field
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub found: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>
+}
+
+// the details are boxed since most of them are empty, unboxed they made every Result carrying the error large
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SimplexError
+{
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Box<ErrorDetails>
+}
+
+impl SimplexError
+{
+    pub fn new(code: ErrorCode, message: String) -> Self
+    {
+        SimplexError { code: code, message: message, details: Box::default() }
+    }
+
+    pub fn internal(message: String) -> Self
+    {
+        SimplexError::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_row(mut self, row: usize) -> Self
+    {
+        self.details.row = Some(row);
+        self
+    }
+
+    pub fn with_column(mut self, column: usize) -> Self
+    {
+        self.details.column = Some(column);
+        self
+    }
+
+    pub fn with_field(mut self, field: &str) -> Self
+    {
+        self.details.field = Some(String::from(field));
+        self
+    }
+
+    pub fn with_lengths(mut self, expected: usize, found: usize) -> Self
+    {
+        self.details.expected = Some(expected);
+        self.details.found = Some(found);
+        self
+    }
+
+    pub fn with_value(mut self, value: f32) -> Self
+    {
+        self.details.value = Some(value);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: &str) -> Self
+    {
+        self.details.limit = Some(String::from(limit));
+        self
+    }
+}
+
+impl Display for SimplexError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// the error a guard turned the request away with, the catchers answer with it instead of a generic message
+struct GuardFailure(Option<SimplexError>);
+
+pub fn remember_failure(request: &Request<'_>, error: &SimplexError)
+{
+    request.local_cache(|| GuardFailure(Some(error.clone())));
+}
+
+pub fn guard_failure(request: &Request<'_>) -> Option<SimplexError>
+{
+    request.local_cache(|| GuardFailure(None)).0.clone()
+}
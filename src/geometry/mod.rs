@@ -0,0 +1,280 @@
+use crate::error::{ErrorCode, SimplexError};
+use crate::row_arithmetic::LinearProgram;
+
+const EPSILON: f64 = 1e-7;
+
+// enumerating vertices tries every choice of hyperplanes, past this many it takes too long to do in a request
+pub const MAX_VERTEX_COMBINATIONS: usize = 100_000;
+
+// normal * x <= offset
+#[derive(Debug, Clone)]
+pub struct Halfspace
+{
+    pub normal: Vec<f64>,
+    pub offset: f64
+}
+
+#[derive(Debug, Clone)]
+pub struct Vertex
+{
+    pub point: Vec<f64>,
+    // indices of the halfspaces which hold with equality at this vertex
+    pub tight: Vec<usize>
+}
+
+// the first m columns are the slacks of the identity basis, the rest are the variables we can draw
+pub fn structural_columns(linear_program: &LinearProgram) -> Vec<usize>
+{
+    (linear_program.tableau.len()..linear_program.costs.len()).collect()
+}
+
+// every row gives a_i * x <= b_i (its slack has to stay positive) and every structural variable gives -x_j <= 0
+pub fn halfspaces(linear_program: &LinearProgram) -> Vec<Halfspace>
+{
+    let columns = structural_columns(linear_program);
+
+    let mut halfspaces: Vec<Halfspace> = linear_program.tableau.iter().map(|row| Halfspace
+    {
+        normal: columns.iter().map(|column| row.a_ij[*column] as f64).collect(),
+        offset: row.b_i as f64
+    }).collect();
+
+    for index in 0..columns.len()
+    {
+        let mut normal = vec![0.0; columns.len()];
+        normal[index] = -1.0;
+        halfspaces.push(Halfspace { normal: normal, offset: 0.0 });
+    }
+
+    halfspaces
+}
+
+// caps every variable at bound, used to draw regions which go off to infinity
+pub fn bounding_halfspaces(dimension: usize, bound: f64) -> Vec<Halfspace>
+{
+    (0..dimension).map(|index|
+    {
+        let mut normal = vec![0.0; dimension];
+        normal[index] = 1.0;
+        Halfspace { normal: normal, offset: bound }
+    }).collect()
+}
+
+// objective in terms of the structural variables, the slacks are replaced by b_i - a_i * x
+pub fn objective_in_structural_space(linear_program: &LinearProgram) -> (Vec<f64>, f64)
+{
+    let columns = structural_columns(linear_program);
+
+    let gradient = columns.iter().map(|column|
+    {
+        linear_program.costs[*column] as f64 - linear_program.tableau.iter().enumerate()
+            .map(|(row_index, row)| linear_program.costs[row_index] as f64 * row.a_ij[*column] as f64).sum::<f64>()
+    }).collect();
+
+    let constant = linear_program.tableau.iter().enumerate()
+        .map(|(row_index, row)| linear_program.costs[row_index] as f64 * row.b_i as f64).sum();
+
+    (gradient, constant)
+}
+
+// how many choices of hyperplanes polytope_vertices has to try
+pub fn vertex_combinations(linear_program: &LinearProgram) -> usize
+{
+    let columns = structural_columns(linear_program).len();
+    combination_count(linear_program.tableau.len() + columns, columns)
+}
+
+// every vertex of the feasible region written as a full solution, the slacks are b_i - a_i * x
+pub fn polytope_vertices(linear_program: &LinearProgram) -> Result<Vec<Vec<f32>>, SimplexError>
+{
+    let columns = structural_columns(linear_program);
+    let halfspaces = halfspaces(linear_program);
+
+    let combinations = vertex_combinations(linear_program);
+    if combinations > MAX_VERTEX_COMBINATIONS
+    {
+        return Err(SimplexError::new(ErrorCode::TooLarge, format!("The problem is too large to enumerate its vertices: combinations = {}, maximum = {}", combinations, MAX_VERTEX_COMBINATIONS))
+            .with_lengths(MAX_VERTEX_COMBINATIONS, combinations));
+    }
+
+    Ok(enumerate_vertices(&halfspaces, columns.len()).iter().map(|vertex|
+    {
+        linear_program.tableau.iter()
+            .map(|row| row.b_i - columns.iter().zip(vertex.point.iter()).map(|(column, x)| row.a_ij[*column] * *x as f32).sum::<f32>())
+            .chain(vertex.point.iter().map(|x| *x as f32)).collect()
+    }).collect())
+}
+
+// n choose k, saturates instead of overflowing
+pub fn combination_count(n: usize, k: usize) -> usize
+{
+    let k = k.min(n.saturating_sub(k));
+    let mut count: usize = 1;
+
+    for index in 0..k
+    {
+        count = count.saturating_mul(n - index) / (index + 1);
+    }
+
+    count
+}
+
+pub fn is_feasible(halfspaces: &Vec<Halfspace>, point: &Vec<f64>) -> bool
+{
+    halfspaces.iter().all(|halfspace| dot(&halfspace.normal, point) <= halfspace.offset + EPSILON)
+}
+
+// brute force over every choice of `dimension` halfspaces, fine for the small problems this is meant for
+pub fn enumerate_vertices(halfspaces: &Vec<Halfspace>, dimension: usize) -> Vec<Vertex>
+{
+    let mut vertices: Vec<Vertex> = Vec::new();
+    add_vertices(halfspaces, dimension, 0, &mut vertices);
+    vertices
+}
+
+// the vertices are those of halfspaces[..added], this adds the ones that only appear with halfspaces[added..] and works
+// out again which halfspaces are tight at the old ones. an old vertex that the added halfspaces cut off is dropped
+pub fn add_vertices(halfspaces: &Vec<Halfspace>, dimension: usize, added: usize, vertices: &mut Vec<Vertex>)
+{
+    vertices.retain(|vertex| is_feasible(halfspaces, &vertex.point));
+    for vertex in vertices.iter_mut()
+    {
+        vertex.tight = tight_halfspaces(halfspaces, &vertex.point);
+    }
+
+    for combination in combinations(halfspaces.len(), dimension).into_iter().filter(|combination| combination.iter().any(|index| *index >= added))
+    {
+        let matrix: Vec<Vec<f64>> = combination.iter().map(|index| halfspaces[*index].normal.clone()).collect();
+        let right_side: Vec<f64> = combination.iter().map(|index| halfspaces[*index].offset).collect();
+
+        let point = match solve_linear_system(matrix, right_side)
+        {
+            Some(point) => point,
+            None => continue
+        };
+
+        if !is_feasible(halfspaces, &point) || vertices.iter().any(|vertex| same_point(&vertex.point, &point))
+        {
+            continue;
+        }
+
+        let tight = tight_halfspaces(halfspaces, &point);
+        vertices.push(Vertex { point: point, tight: tight });
+    }
+}
+
+fn tight_halfspaces(halfspaces: &Vec<Halfspace>, point: &Vec<f64>) -> Vec<usize>
+{
+    halfspaces.iter().enumerate()
+        .filter(|(_, halfspace)| (dot(&halfspace.normal, point) - halfspace.offset).abs() <= EPSILON)
+        .map(|(index, _)| index).collect()
+}
+
+pub fn same_point(point_1: &Vec<f64>, point_2: &Vec<f64>) -> bool
+{
+    point_1.iter().zip(point_2.iter()).all(|(x, y)| (x - y).abs() <= EPSILON)
+}
+
+pub fn dot(vector_1: &Vec<f64>, vector_2: &Vec<f64>) -> f64
+{
+    vector_1.iter().zip(vector_2.iter()).map(|(x, y)| x * y).sum()
+}
+
+// all subsets of size k of 0..n in lexicographic order
+pub fn combinations(n: usize, k: usize) -> Vec<Vec<usize>>
+{
+    let mut result: Vec<Vec<usize>> = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    extend_combination(n, k, 0, &mut current, &mut result);
+    result
+}
+
+fn extend_combination(n: usize, k: usize, start: usize, current: &mut Vec<usize>, result: &mut Vec<Vec<usize>>)
+{
+    if current.len() == k
+    {
+        result.push(current.clone());
+        return;
+    }
+
+    for index in start..n
+    {
+        current.push(index);
+        extend_combination(n, k, index + 1, current, result);
+        current.pop();
+    }
+}
+
+// number of linearly independent rows, found by elimination with partial pivoting
+pub fn matrix_rank(mut matrix: Vec<Vec<f64>>) -> usize
+{
+    let columns = matrix.first().map_or(0, |row| row.len());
+    let mut rank: usize = 0;
+
+    for column in 0..columns
+    {
+        if rank == matrix.len()
+        {
+            break;
+        }
+
+        let pivot_row = match (rank..matrix.len()).max_by(|x, y| matrix[*x][column].abs().partial_cmp(&matrix[*y][column].abs())
+            .unwrap_or(std::cmp::Ordering::Equal))
+        {
+            Some(row) if matrix[row][column].abs() > EPSILON => row,
+            _ => continue
+        };
+
+        matrix.swap(rank, pivot_row);
+
+        for row in rank + 1..matrix.len()
+        {
+            let multiplier = matrix[row][column] / matrix[rank][column];
+            for inner_column in column..columns
+            {
+                matrix[row][inner_column] -= multiplier * matrix[rank][inner_column];
+            }
+        }
+
+        rank += 1;
+    }
+
+    rank
+}
+
+// gaussian elimination with partial pivoting, None if the system is singular
+pub fn solve_linear_system(mut matrix: Vec<Vec<f64>>, mut right_side: Vec<f64>) -> Option<Vec<f64>>
+{
+    let size = right_side.len();
+
+    for column in 0..size
+    {
+        let pivot_row = (column..size).max_by(|x, y| matrix[*x][column].abs().partial_cmp(&matrix[*y][column].abs())
+            .unwrap_or(std::cmp::Ordering::Equal))?;
+
+        if matrix[pivot_row][column].abs() <= EPSILON
+        {
+            return None;
+        }
+
+        matrix.swap(column, pivot_row);
+        right_side.swap(column, pivot_row);
+
+        for row in 0..size
+        {
+            if row == column
+            {
+                continue;
+            }
+
+            let multiplier = matrix[row][column] / matrix[column][column];
+            for inner_column in column..size
+            {
+                matrix[row][inner_column] -= multiplier * matrix[column][inner_column];
+            }
+            right_side[row] -= multiplier * right_side[column];
+        }
+    }
+
+    Some((0..size).map(|index| right_side[index] / matrix[index][index]).collect())
+}
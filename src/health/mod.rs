@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use rocket::http::Status;
+use rocket::response::status;
+use rocket::serde::Serialize;
+use rocket::serde::json::Json;
+use rocket::figment::Figment;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::{Request, Route, State};
+use schemars::JsonSchema;
+
+use crate::config::{AuthConfig, BatchConfig, CacheConfig, JobsConfig, StoreConfig};
+use crate::row_arithmetic::{LinearProgram, PivotRule, Row, SolveLimits};
+use crate::store::Store;
+use crate::{LinearProgramResponse, SolveOptions};
+
+const SELF_TEST_OBJECTIVE: f32 = 2.0;
+const SELF_TEST_TIME_LIMIT: Duration = Duration::from_secs(1);
+
+// these are always mounted and have no setting to turn them off
+const BUILT_IN_FEATURES: [&str; 7] = ["alternative_optima", "vertices", "visualize", "render", "validate", "streaming", "store"];
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Liveness
+{
+    pub status: &'static str
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Check
+{
+    pub name: &'static str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub elapsed_ms: f64
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Readiness
+{
+    pub ready: bool,
+    pub checks: Vec<Check>
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct VersionInfo
+{
+    pub name: &'static str,
+    pub version: &'static str,
+    // only there when GIT_COMMIT was set for the build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<&'static str>,
+    pub pivot_rules: Vec<PivotRule>,
+    pub features: Vec<&'static str>
+}
+
+fn check(name: &'static str, test: impl FnOnce() -> Result<(), String>) -> Check
+{
+    let start = Instant::now();
+    let result = test();
+
+    Check { name: name, ok: result.is_ok(), message: result.err(), elapsed_ms: start.elapsed().as_secs_f64() * 1000.0 }
+}
+
+// the problem of test_all_checks_passed_has_optimal_solution, it takes a pivot on each row to get to the optimum
+fn self_test_problem() -> LinearProgram
+{
+    LinearProgram
+    {
+        tableau: vec![Row { a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 1.0 }, Row { a_ij: vec![0.0, 1.0, 2.0, 1.0], b_i: 1.0 }],
+        costs: vec![0.0, 0.0, 1.0, 2.0],
+        relative_costs: Row { a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0 },
+        solution: vec![1.0, 1.0, 0.0, 0.0]
+    }
+}
+
+// goes around solve_linear_program so the probes of the orchestrator do not show up in the solve metrics
+pub fn self_test() -> Result<(), String>
+{
+    let options = SolveOptions
+    {
+        limits: SolveLimits { max_iterations: Some(100), time_limit: Some(SELF_TEST_TIME_LIMIT), cancelled: None, shutdown: None },
+        ..SolveOptions::default()
+    };
+
+    match crate::run_solver(self_test_problem(), &options, &mut |_, _| ())
+    {
+        LinearProgramResponse::LinearProgram(report) if (report.row.b_i - SELF_TEST_OBJECTIVE).abs() < 1e-4 => Ok(()),
+        LinearProgramResponse::LinearProgram(report) => Err(format!("The self-test problem gave the objective {} instead of {}", report.row.b_i, SELF_TEST_OBJECTIVE)),
+        response => Err(format!("The self-test problem should be optimal but was {}", response.outcome()))
+    }
+}
+
+// answers as long as the server takes requests at all
+#[get("/health")]
+fn health() -> Json<Liveness>
+{
+    Json(Liveness { status: "ok" })
+}
+
+// 503 until everything a solve needs works, the report says which check failed
+#[get("/ready")]
+async fn ready(store: &State<Store>) -> status::Custom<Json<Readiness>>
+{
+    let mut checks = Vec::new();
+
+    let start = Instant::now();
+    checks.push(match rocket::tokio::task::spawn_blocking(self_test).await
+    {
+        Ok(result) => Check { name: "solver", ok: result.is_ok(), message: result.err(), elapsed_ms: start.elapsed().as_secs_f64() * 1000.0 },
+        Err(error) => Check { name: "solver", ok: false, message: Some(format!("The self-test panicked: {}", error)), elapsed_ms: start.elapsed().as_secs_f64() * 1000.0 }
+    });
+
+    // sled reads from disk, which should not hold up the other requests on this worker
+    let start = Instant::now();
+    let store = store.inner().clone();
+    checks.push(match rocket::tokio::task::spawn_blocking(move || check("store", || store.check())).await
+    {
+        Ok(check) => check,
+        Err(error) => Check { name: "store", ok: false, message: Some(format!("The store check panicked: {}", error)), elapsed_ms: start.elapsed().as_secs_f64() * 1000.0 }
+    });
+
+    let ready = checks.iter().all(|check| check.ok);
+    if !ready
+    {
+        tracing::warn!(failed = ?checks.iter().filter(|check| !check.ok).map(|check| check.name).collect::<Vec<_>>(), "not ready");
+    }
+
+    let status = if ready { Status::Ok } else { Status::ServiceUnavailable };
+    status::Custom(status, Json(Readiness { ready: ready, checks: checks }))
+}
+
+// the features the tables in rocket.toml turn on, read the same way the server reads them when it starts
+fn features(figment: &Figment) -> Vec<&'static str>
+{
+    let jobs_config: JobsConfig = figment.extract_inner("jobs").unwrap_or_default();
+    let batch_config: BatchConfig = figment.extract_inner("batch").unwrap_or_default();
+    let store_config: StoreConfig = figment.extract_inner("store").unwrap_or_default();
+    let cache_config: CacheConfig = figment.extract_inner("cache").unwrap_or_default();
+    let auth_config: AuthConfig = figment.extract_inner("auth").unwrap_or_default();
+
+    let mut features = BUILT_IN_FEATURES.to_vec();
+
+    // there is always at least one worker
+    features.push("jobs");
+    if jobs_config.persist
+    {
+        features.push("persisted_jobs");
+    }
+
+    if batch_config.max_problems > 0
+    {
+        features.push("batch");
+    }
+
+    if cache_config.capacity > 0
+    {
+        features.push(if cache_config.persist { "persisted_cache" } else { "cache" });
+    }
+
+    if store_config.save_solves
+    {
+        features.push("save_solves");
+    }
+
+    if auth_config.enabled
+    {
+        features.push("api_keys");
+    }
+
+    features
+}
+
+// the features of the server the request came to
+pub struct Features(Vec<&'static str>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Features
+{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, ()>
+    {
+        Outcome::Success(Features(features(request.rocket().figment())))
+    }
+}
+
+#[get("/version")]
+fn version(features: Features) -> Json<VersionInfo>
+{
+    Json(VersionInfo
+    {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        commit: option_env!("GIT_COMMIT"),
+        pivot_rules: vec![PivotRule::Lexicographic, PivotRule::Bland, PivotRule::Dantzig],
+        features: features.0
+    })
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![health, ready, version]
+}
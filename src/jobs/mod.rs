@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rocket::http::Status;
+use rocket::response::status;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use schemars::JsonSchema;
+
+use crate::auth::{ApiKeys, Caller};
+use crate::config::{JobsConfig, SolverConfig};
+use crate::logging::RequestId;
+use crate::error::{ErrorCode, SimplexError};
+use crate::parse::JsonBody;
+use crate::row_arithmetic::{self, LinearProgram, PivotRule};
+use crate::v1::RouteError;
+use crate::{shutdown, LimitsQuery, LinearProgramResponse, SolveOptions};
+
+// how long a worker waits for a job before it lets go of the queue, persist takes the queue in between
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(100);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum JobStatus
+{
+    Queued,
+    Running,
+    Finished,
+    Failed,
+    Cancelled
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct JobProgress
+{
+    pub iterations: usize,
+    pub objective: f32
+}
+
+#[derive(Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct JobInfo
+{
+    pub id: u64,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    // seconds since the unix epoch
+    pub submitted_at: u64,
+    pub finished_at: Option<u64>
+}
+
+struct Job
+{
+    info: JobInfo,
+    result: Option<LinearProgramResponse>,
+    cancelled: Arc<AtomicBool>,
+    // the name of the key that submitted the job, only that key sees it
+    owner: Option<String>
+}
+
+struct QueuedJob
+{
+    id: u64,
+    linear_program: LinearProgram,
+    options: SolveOptions,
+    caller: Caller,
+    // the span of the request that submitted the job. the job outlives that request, so its span follows from it
+    // instead of being a child of it
+    span: tracing::Span
+}
+
+// what a queued job needs to be queued again after a restart, the caller is kept by the name of its key
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct PersistedJob
+{
+    id: u64,
+    submitted_at: u64,
+    linear_program: LinearProgram,
+    pivot_rule: PivotRule,
+    max_iterations: Option<usize>,
+    time_limit_ms: Option<u64>,
+    key: Option<String>
+}
+
+// solves run on their own threads so a long solve never holds up one of Rocket's workers, a clone is the same queue
+#[derive(Clone)]
+pub struct JobQueue
+{
+    jobs: Arc<Mutex<HashMap<u64, Job>>>,
+    sender: SyncSender<QueuedJob>,
+    receiver: Arc<Mutex<Receiver<QueuedJob>>>,
+    // jobs a worker took off the queue after the server started draining and the ones the shutdown stopped while they
+    // ran, they are persisted rather than run
+    parked: Arc<Mutex<Vec<QueuedJob>>>,
+    next_id: Arc<AtomicU64>,
+    retained_jobs: usize
+}
+
+fn lock(jobs: &Mutex<HashMap<u64, Job>>) -> MutexGuard<'_, HashMap<u64, Job>>
+{
+    // a worker that panicked mid update leaves nothing half written that matters more than the other jobs
+    jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl JobQueue
+{
+    pub fn new(config: &JobsConfig) -> Self
+    {
+        JobQueue::with_workers(config, config.workers.max(1))
+    }
+
+    // a queue without workers keeps its jobs queued, which is how the tests look at what persist writes
+    pub(crate) fn with_workers(config: &JobsConfig, workers: usize) -> Self
+    {
+        let (sender, receiver) = mpsc::sync_channel::<QueuedJob>(config.queue_capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let jobs: Arc<Mutex<HashMap<u64, Job>>> = Arc::new(Mutex::new(HashMap::new()));
+        let parked: Arc<Mutex<Vec<QueuedJob>>> = Arc::new(Mutex::new(Vec::new()));
+
+        for _ in 0..workers
+        {
+            let receiver = receiver.clone();
+            let jobs = jobs.clone();
+            let parked = parked.clone();
+            thread::spawn(move || work(receiver, jobs, parked));
+        }
+
+        JobQueue { jobs: jobs, sender: sender, receiver: receiver, parked: parked, next_id: Arc::new(AtomicU64::new(1)), retained_jobs: config.retained_jobs }
+    }
+
+    pub fn submit(&self, linear_program: LinearProgram, options: SolveOptions, caller: Caller) -> Result<JobInfo, SimplexError>
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.enqueue(id, crate::unix_time(), linear_program, options, caller)
+    }
+
+    fn enqueue(&self, id: u64, submitted_at: u64, linear_program: LinearProgram, mut options: SolveOptions, caller: Caller) -> Result<JobInfo, SimplexError>
+    {
+        if shutdown::global().draining()
+        {
+            return Err(SimplexError::new(ErrorCode::ShuttingDown, format!("The server is shutting down, submit the job again later")));
+        }
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        options.limits.cancelled = Some(cancelled.clone());
+
+        let info = JobInfo
+        {
+            id: id, status: JobStatus::Queued, progress: JobProgress { iterations: 0, objective: 0.0 },
+            submitted_at: submitted_at, finished_at: None
+        };
+
+        {
+            let mut jobs = lock(&self.jobs);
+            forget_oldest_finished(&mut jobs, self.retained_jobs);
+            jobs.insert(id, Job { info: info.clone(), result: None, cancelled: cancelled, owner: caller.name().map(String::from) });
+        }
+
+        match self.sender.try_send(QueuedJob { id: id, linear_program: linear_program, options: options, caller: caller, span: tracing::Span::current() })
+        {
+            Ok(_) =>
+            {
+                // logged under the request id, the job's own lines carry the same job id
+                tracing::info!(id = id, "queued the job");
+                Ok(info)
+            },
+            Err(error) =>
+            {
+                lock(&self.jobs).remove(&id);
+                match error
+                {
+                    TrySendError::Full(_) => Err(SimplexError::new(ErrorCode::QueueFull, format!("The job queue is full, try again later"))),
+                    TrySendError::Disconnected(_) => Err(SimplexError::internal(format!("No workers are running to take the job")))
+                }
+            }
+        }
+    }
+
+    // a job that belongs to another key is answered like one that does not exist
+    pub fn info(&self, id: u64, caller: &Caller) -> Option<JobInfo>
+    {
+        lock(&self.jobs).get(&id).filter(|job| caller.owns(job.owner.as_deref())).map(|job| job.info.clone())
+    }
+
+    pub fn result(&self, id: u64, caller: &Caller) -> Option<(JobInfo, Option<LinearProgramResponse>)>
+    {
+        lock(&self.jobs).get(&id).filter(|job| caller.owns(job.owner.as_deref())).map(|job| (job.info.clone(), job.result.clone()))
+    }
+
+    // a queued job is cancelled straight away, a running one stops at its next iteration
+    pub fn cancel(&self, id: u64, caller: &Caller) -> Option<JobInfo>
+    {
+        let mut jobs = lock(&self.jobs);
+        let job = jobs.get_mut(&id).filter(|job| caller.owns(job.owner.as_deref()))?;
+
+        job.cancelled.store(true, Ordering::Relaxed);
+        if job.info.status == JobStatus::Queued
+        {
+            job.info.status = JobStatus::Cancelled;
+            job.info.finished_at = Some(crate::unix_time());
+        }
+
+        Some(job.info.clone())
+    }
+
+    // a job the shutdown stopped is only parked by its worker once its solve has returned, so persist is called after
+    // the running ones are done or the timeout is over
+    pub fn wait_for_running(&self, timeout: Duration)
+    {
+        let deadline = Instant::now() + timeout;
+        while lock(&self.jobs).values().any(|job| job.info.status == JobStatus::Running) && Instant::now() < deadline
+        {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    // called once the server has stopped taking jobs, every job still waiting goes into the tree under its id
+    pub fn persist(&self, tree: &sled::Tree) -> Result<usize, String>
+    {
+        let mut queued: Vec<QueuedJob> = self.parked.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).drain(..).collect();
+        queued.extend(self.receiver.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).try_iter());
+
+        let jobs = lock(&self.jobs);
+        let mut persisted = 0;
+
+        // a job cancelled while it waited has nothing left to run
+        for queued in queued.into_iter().filter(|queued| jobs.get(&queued.id).map_or(false, |job| job.info.status == JobStatus::Queued))
+        {
+            let job = PersistedJob
+            {
+                id: queued.id,
+                submitted_at: jobs[&queued.id].info.submitted_at,
+                pivot_rule: queued.options.pivot_rule,
+                max_iterations: queued.options.limits.max_iterations,
+                time_limit_ms: queued.options.limits.time_limit.map(|x| x.as_millis() as u64),
+                key: queued.caller.name().map(String::from),
+                linear_program: queued.linear_program
+            };
+
+            let bytes = serde_json::to_vec(&job).map_err(|x| format!("Could not write job {}: {}", job.id, x))?;
+            tree.insert(job.id.to_be_bytes(), bytes).map_err(|x| format!("Could not write job {}: {}", job.id, x))?;
+            persisted += 1;
+        }
+
+        tree.flush().map_err(|x| format!("Could not write the jobs to disk: {}", x))?;
+        Ok(persisted)
+    }
+
+    // queues the jobs persist kept under the ids they had, the ones handed out later follow on from them
+    pub fn restore(&self, tree: &sled::Tree, api_keys: &ApiKeys) -> Result<usize, String>
+    {
+        let mut restored = 0;
+
+        for entry in tree.iter()
+        {
+            let (key, bytes) = entry.map_err(|x| format!("Could not read the queued jobs: {}", x))?;
+
+            let job: PersistedJob = match serde_json::from_slice(&bytes)
+            {
+                Ok(job) => job,
+                Err(error) =>
+                {
+                    tracing::warn!(%error, "dropped a queued job that could not be read");
+                    tree.remove(&key).map_err(|x| format!("Could not remove a queued job: {}", x))?;
+                    continue;
+                }
+            };
+
+            let options = SolveOptions
+            {
+                pivot_rule: job.pivot_rule,
+                limits: row_arithmetic::SolveLimits
+                {
+                    max_iterations: job.max_iterations,
+                    time_limit: job.time_limit_ms.map(Duration::from_millis),
+                    ..row_arithmetic::SolveLimits::default()
+                },
+                ..SolveOptions::default()
+            };
+
+            self.next_id.fetch_max(job.id + 1, Ordering::Relaxed);
+            match self.enqueue(job.id, job.submitted_at, job.linear_program, options, api_keys.caller_named(job.key.as_deref()))
+            {
+                Ok(_) => restored += 1,
+                Err(error) =>
+                {
+                    // the jobs are kept in order of their ids, so the last one left is the highest and new jobs are numbered after it
+                    if let Ok(Some((last, _))) = tree.last()
+                    {
+                        let last: [u8; 8] = last.as_ref().try_into().unwrap_or_default();
+                        self.next_id.fetch_max(u64::from_be_bytes(last) + 1, Ordering::Relaxed);
+                    }
+                    tracing::warn!(id = job.id, message = %error.message, left = tree.len(), "could not queue a kept job again, the rest stay kept until the next start");
+                    break;
+                }
+            };
+            tree.remove(&key).map_err(|x| format!("Could not remove a queued job: {}", x))?;
+        }
+
+        Ok(restored)
+    }
+}
+
+fn forget_oldest_finished(jobs: &mut HashMap<u64, Job>, retained_jobs: usize)
+{
+    let mut finished: Vec<u64> = jobs.iter()
+        .filter(|(_, job)| job.info.finished_at.is_some())
+        .map(|(id, _)| *id).collect();
+
+    if finished.len() < retained_jobs
+    {
+        return;
+    }
+
+    finished.sort();
+    for id in finished.iter().take(finished.len() + 1 - retained_jobs.max(1))
+    {
+        jobs.remove(id);
+    }
+}
+
+fn work(receiver: Arc<Mutex<Receiver<QueuedJob>>>, jobs: Arc<Mutex<HashMap<u64, Job>>>, parked: Arc<Mutex<Vec<QueuedJob>>>)
+{
+    loop
+    {
+        // the lock is only held while waiting for a little while, a worker blocked on an empty queue would keep persist
+        // from ever taking it
+        let received = receiver.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).recv_timeout(RECEIVE_TIMEOUT);
+        let queued = match received
+        {
+            Ok(queued) => queued,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return
+        };
+
+        if shutdown::global().draining()
+        {
+            parked.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(queued);
+            continue;
+        }
+
+        let span = tracing::info_span!(parent: None, "job", id = queued.id);
+        span.follows_from(&queued.span);
+        let _entered = span.enter();
+
+        match lock(&jobs).get_mut(&queued.id)
+        {
+            Some(job) if job.info.status == JobStatus::Queued => job.info.status = JobStatus::Running,
+            _ => continue
+        };
+
+        let progress_jobs = jobs.clone();
+        let start = Instant::now();
+        // a solve that panics fails its job instead of taking the worker down with the job left running
+        let solved = panic::catch_unwind(AssertUnwindSafe(|| crate::solve_linear_program_observed(queued.linear_program.clone(), &queued.options, &mut |event, _|
+        {
+            if let Some(job) = lock(&progress_jobs).get_mut(&queued.id)
+            {
+                job.info.progress = JobProgress { iterations: event.iteration, objective: event.objective };
+            }
+        })));
+
+        let response = match solved
+        {
+            Ok(LinearProgramResponse::LimitReached(report)) if report.reason == row_arithmetic::Limit::Shutdown =>
+            {
+                // the job was not finished, so it is queued again and kept with the waiting ones to run after the restart
+                if let Some(job) = lock(&jobs).get_mut(&queued.id)
+                {
+                    job.info.status = JobStatus::Queued;
+                    job.info.progress = JobProgress { iterations: 0, objective: 0.0 };
+                }
+                parked.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(queued);
+                continue;
+            },
+            Ok(response) =>
+            {
+                queued.caller.record(&response, start.elapsed());
+                response
+            },
+            Err(_) => LinearProgramResponse::Error(SimplexError::internal(format!("The solver stopped unexpectedly")))
+        };
+
+        let status = match &response
+        {
+            LinearProgramResponse::Error(_) => JobStatus::Failed,
+            LinearProgramResponse::LimitReached(report) if report.reason == row_arithmetic::Limit::Cancelled => JobStatus::Cancelled,
+            _ => JobStatus::Finished
+        };
+
+        if let Some(job) = lock(&jobs).get_mut(&queued.id)
+        {
+            job.info.status = status;
+            job.info.finished_at = Some(crate::unix_time());
+            job.result = Some(response);
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct JobResult
+{
+    pub job: JobInfo,
+    pub result: Option<LinearProgramResponse>
+}
+
+#[post("/jobs?<limits..>", data = "<linear_program>")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0))]
+fn submit_job(linear_program: JsonBody<LinearProgram>, limits: LimitsQuery, request_id: RequestId, caller: Caller, job_queue: &State<JobQueue>, solver_config: &State<SolverConfig>) -> Result<status::Custom<Json<JobInfo>>, RouteError>
+{
+    if let Err(error) = caller.check(&linear_program)
+    {
+        return Err(RouteError::new(Status::PayloadTooLarge, error));
+    }
+
+    let options = limits.options(solver_config, None);
+
+    match job_queue.submit(linear_program.into_inner(), options, caller)
+    {
+        Ok(info) => Ok(status::Custom(Status::Accepted, Json(info))),
+        Err(error) => Err(RouteError::new(Status::ServiceUnavailable, error))
+    }
+}
+
+#[get("/jobs/<id>")]
+fn job_status(id: u64, caller: Caller, job_queue: &State<JobQueue>) -> Option<Json<JobInfo>>
+{
+    job_queue.info(id, &caller).map(Json)
+}
+
+// the result is null until the job stops running
+#[get("/jobs/<id>/result")]
+fn job_result(id: u64, caller: Caller, job_queue: &State<JobQueue>) -> Option<Json<JobResult>>
+{
+    job_queue.result(id, &caller).map(|(info, result)| Json(JobResult { job: info, result: result }))
+}
+
+#[delete("/jobs/<id>")]
+fn cancel_job(id: u64, caller: Caller, job_queue: &State<JobQueue>) -> Option<Json<JobInfo>>
+{
+    job_queue.cancel(id, &caller).map(Json)
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![submit_job, job_status, job_result, cancel_job]
+}
@@ -0,0 +1,807 @@
+// the code spells out field names, builds its messages with format! and writes out its matches and index loops, clippy's
+// style lints for those are turned off rather than rewriting the crate against its own conventions
+#![allow(clippy::redundant_field_names, clippy::useless_format, clippy::assertions_on_constants, clippy::ptr_arg,
+    clippy::needless_range_loop, clippy::single_match, clippy::len_zero, clippy::unnecessary_map_or, clippy::manual_is_multiple_of)]
+
+#[macro_use] extern crate rocket;
+
+pub mod error;
+pub mod row_arithmetic;
+use rocket::serde::json::Json;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use rocket::http::{Accept, ContentType, Header, Status};
+use rocket::response::content::RawHtml;
+use rocket::response::status;
+use rocket::State;
+use rocket::fairing::AdHoc;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rocket::futures::Stream;
+use rocket::response::stream::ByteStream;
+use rocket::response::{self, Responder, Response};
+use rocket::Request;
+use tracing::Instrument;
+
+mod auth;
+mod batch;
+mod cache;
+mod cli;
+mod config;
+mod cors;
+mod geometry;
+mod health;
+mod jobs;
+mod limits;
+mod logging;
+pub mod lp_file;
+mod metrics;
+pub mod model;
+pub mod mps;
+mod openapi;
+pub mod parse;
+pub mod render;
+pub mod repl;
+mod shutdown;
+mod simplex_test;
+mod store;
+mod streaming;
+mod v1;
+mod validate;
+mod visualize;
+
+
+#[get("/")]
+fn hello_world() -> Json<String>
+{
+    Json(String::from("Hello world"))
+}
+
+// at most this many optimal vertices are reported when alternatives are asked for, the first one included
+const MAX_ALTERNATIVE_OPTIMA: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub struct SolutionReport
+{
+    #[serde(flatten)]
+    pub row: row_arithmetic::Row,
+    pub multiple_optima: bool,
+    pub statistics: row_arithmetic::SimplexStatistics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alternative_optima: Option<Vec<Vec<f32>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertices: Option<Vec<Vec<f32>>>
+}
+
+impl SolutionReport
+{
+    pub fn new(row: row_arithmetic::Row, multiple_optima: bool, statistics: row_arithmetic::SimplexStatistics) -> Self
+    {
+        SolutionReport { row: row, multiple_optima: multiple_optima, statistics: statistics, alternative_optima: None, vertices: None }
+    }
+}
+
+// the solve was stopped early, the solution is the basis it had reached by then
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub struct LimitReport
+{
+    #[serde(flatten)]
+    pub row: row_arithmetic::Row,
+    pub reason: row_arithmetic::Limit,
+    pub statistics: row_arithmetic::SimplexStatistics
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub enum LinearProgramResponse
+{
+    LinearProgram(SolutionReport),
+    Unbound(String),
+    LimitReached(LimitReport),
+    Error(error::SimplexError)
+}
+
+impl LinearProgramResponse
+{
+    // the name a response is counted and logged under, a starting solution that is not feasible is turned away by the
+    // checks so infeasible is told apart by that error
+    pub fn outcome(&self) -> &'static str
+    {
+        match self
+        {
+            LinearProgramResponse::LinearProgram(_) => "optimal",
+            LinearProgramResponse::Unbound(_) => "unbounded",
+            LinearProgramResponse::LimitReached(_) => "limit_reached",
+            LinearProgramResponse::Error(error) if error.code == error::ErrorCode::InfeasibleSolution => "infeasible",
+            LinearProgramResponse::Error(_) => "error"
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SolveOptions
+{
+    pub alternatives: bool,
+    pub vertices: bool,
+    pub pivot_rule: row_arithmetic::PivotRule,
+    pub limits: row_arithmetic::SolveLimits
+}
+
+impl Default for SolveOptions
+{
+    fn default() -> Self
+    {
+        SolveOptions { alternatives: false, vertices: false, pivot_rule: row_arithmetic::PivotRule::Lexicographic, limits: row_arithmetic::SolveLimits::default() }
+    }
+}
+
+// the query of the routes that only solve, e.g. /jobs?pivot_rule=bland&max_iterations=100. left out limits are the
+// ones in the [global.solver] table, and asking for more than those gets those
+#[derive(FromForm)]
+pub struct LimitsQuery
+{
+    pub pivot_rule: Option<row_arithmetic::PivotRule>,
+    pub max_iterations: Option<usize>,
+    pub time_limit_ms: Option<u64>
+}
+
+impl LimitsQuery
+{
+    pub const FIELDS: [&'static str; 3] = ["pivot_rule", "max_iterations", "time_limit_ms"];
+
+    pub fn options(&self, solver_config: &config::SolverConfig, cancelled: Option<Arc<AtomicBool>>) -> SolveOptions
+    {
+        SolveOptions
+        {
+            pivot_rule: self.pivot_rule.unwrap_or(row_arithmetic::PivotRule::Lexicographic),
+            limits: solver_config.limits(self.max_iterations, self.time_limit_ms, cancelled),
+            ..SolveOptions::default()
+        }
+    }
+}
+
+// the query of / and /v1/solve, which can also ask for the alternative optima and the vertices
+#[derive(FromForm)]
+pub struct SolveQuery
+{
+    pub alternatives: Option<bool>,
+    pub vertices: Option<bool>,
+    pub pivot_rule: Option<row_arithmetic::PivotRule>,
+    pub max_iterations: Option<usize>,
+    pub time_limit_ms: Option<u64>
+}
+
+impl SolveQuery
+{
+    pub const FIELDS: [&'static str; 5] = ["alternatives", "vertices", "pivot_rule", "max_iterations", "time_limit_ms"];
+
+    pub fn options(&self, solver_config: &config::SolverConfig, cancelled: Option<Arc<AtomicBool>>) -> SolveOptions
+    {
+        SolveOptions
+        {
+            alternatives: self.alternatives.unwrap_or(false),
+            vertices: self.vertices.unwrap_or(false),
+            pivot_rule: self.pivot_rule.unwrap_or(row_arithmetic::PivotRule::Lexicographic),
+            limits: solver_config.limits(self.max_iterations, self.time_limit_ms, cancelled)
+        }
+    }
+}
+
+// sets the flag when the response body is dropped so that a solve nobody is waiting for stops at its next iteration
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop
+{
+    fn drop(&mut self)
+    {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// Rocket never drops a handler when its client disconnects, only a write to the closed connection failing tells. so
+// answers that can take long are streamed with a space every HEARTBEAT until the json is ready, json allows the spaces
+// in front of the value and Rocket drops the stream, and with it the CancelOnDrop, after the first failed write
+const HEARTBEAT: Duration = Duration::from_secs(1);
+
+pub struct JsonStream<'r>(ByteStream<Pin<Box<dyn Stream<Item = Vec<u8>> + Send + 'r>>>);
+
+// written out since a streamed body borrows from the request, which the derive does not allow for
+impl<'r> Responder<'r, 'r> for JsonStream<'r>
+{
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'r>
+    {
+        Response::build_from(self.0.respond_to(request)?).header(ContentType::JSON).ok()
+    }
+}
+
+pub fn stream_answer<'r, T: Serialize + Send + 'r>(cancelled: Arc<AtomicBool>, answer: impl Future<Output = T> + Send + 'r) -> JsonStream<'r>
+{
+    let stream = ByteStream!
+    {
+        let _cancel = CancelOnDrop(cancelled);
+        let mut answer = Box::pin(answer);
+        let mut heartbeat = rocket::tokio::time::interval(HEARTBEAT);
+        // the first tick is right away, a quick answer goes out without any space in front
+        heartbeat.tick().await;
+
+        loop
+        {
+            let value = rocket::tokio::select!
+            {
+                value = &mut answer => Some(value),
+                _ = heartbeat.tick() => None
+            };
+
+            match value
+            {
+                Some(value) =>
+                {
+                    yield serde_json::to_vec(&value).unwrap_or_default();
+                    break;
+                },
+                None => yield b" ".to_vec()
+            };
+        }
+    };
+
+    JsonStream(ByteStream(Box::pin(stream.0)))
+}
+
+pub fn unix_time() -> u64
+{
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0)
+}
+
+pub fn solve_linear_program(linear_program: row_arithmetic::LinearProgram, options: &SolveOptions) -> LinearProgramResponse
+{
+    solve_linear_program_observed(linear_program, options, &mut |_, _| ())
+}
+
+// every way of solving a problem ends up here, which is what makes it the place the solver metrics are taken
+pub fn solve_linear_program_observed(linear_program: row_arithmetic::LinearProgram, options: &SolveOptions, 
+    observer: &mut dyn FnMut(&row_arithmetic::IterationEvent, &row_arithmetic::LinearProgram)) -> LinearProgramResponse
+{
+    let span = tracing::info_span!("solve", rows = linear_program.tableau.len(), columns = linear_program.costs.len(), pivot_rule = ?options.pivot_rule);
+    let _entered = span.enter();
+
+    let metrics = metrics::global();
+    let _in_flight = metrics.start_solve(&linear_program);
+    let start = Instant::now();
+
+    let response = run_solver(linear_program, options, observer);
+    metrics.finish_solve(&response, start.elapsed());
+
+    match &response
+    {
+        LinearProgramResponse::Error(error) => tracing::info!(outcome = response.outcome(), code = ?error.code, message = %error.message, "solve finished"),
+        _ => tracing::info!(outcome = response.outcome(), iterations = auth::iterations(&response), "solve finished")
+    };
+
+    response
+}
+
+pub fn run_solver(mut linear_program: row_arithmetic::LinearProgram, options: &SolveOptions, 
+    observer: &mut dyn FnMut(&row_arithmetic::IterationEvent, &row_arithmetic::LinearProgram)) -> LinearProgramResponse
+{
+    // once the server is shutting down only the solves already running get to finish
+    if shutdown::global().draining()
+    {
+        return LinearProgramResponse::Error(error::SimplexError::new(error::ErrorCode::ShuttingDown, format!("The server is shutting down, try again later")));
+    }
+
+    match row_arithmetic::perform_checks(&linear_program)
+    {
+        Ok(_) => (),
+        Err(error) => return LinearProgramResponse::Error(error)
+    }
+
+    // the vertices are found from the starting tableau since it still has the identity in front
+    let vertices = if options.vertices
+    {
+        match geometry::polytope_vertices(&linear_program)
+        {
+            Ok(vertices) => Some(vertices),
+            Err(error) => return LinearProgramResponse::Error(error)
+        }
+    }
+    else 
+    {
+        None
+    };
+
+    linear_program.relative_costs = linear_program.calculate_costs();
+
+    let limits = row_arithmetic::SolveLimits { shutdown: Some(shutdown::global().stopping()), ..options.limits.clone() };
+    let (result, statistics) = linear_program.preform_simplex_observed(options.pivot_rule, &limits, observer);
+
+    match result
+    {
+        row_arithmetic::SimplexResult::Finished => (),
+        row_arithmetic::SimplexResult::Unbound => return LinearProgramResponse::Unbound(format!("Problem is unbound and the optimal solution is infinity")),
+        row_arithmetic::SimplexResult::LimitReached(limit) => 
+        {
+            let row = row_arithmetic::Row{a_ij: linear_program.basic_solution(), b_i: linear_program.relative_costs.b_i};
+            return LinearProgramResponse::LimitReached(LimitReport { row: row, reason: limit, statistics: statistics });
+        },
+        row_arithmetic::SimplexResult::IterationComplete => return LinearProgramResponse::Error(error::SimplexError::internal(format!("Iteration complete, you should never get this though"))),
+        row_arithmetic::SimplexResult::Error(error) => return LinearProgramResponse::Error(error)
+    };
+
+    let alternative_optima = if options.alternatives
+    {
+        match linear_program.enumerate_optimal_solutions(MAX_ALTERNATIVE_OPTIMA, &limits)
+        {
+            Ok(solutions) => Some(solutions),
+            Err(error) => return LinearProgramResponse::Error(error)
+        }
+    }
+    else 
+    {
+        None
+    };
+
+    match linear_program.set_solution()
+    {
+        Ok(_) => (),
+        Err(error) => return LinearProgramResponse::Error(error)
+    }
+
+    let multiple_optima = linear_program.has_multiple_optima();
+    let response_row = row_arithmetic::Row{a_ij: linear_program.solution, b_i: linear_program.relative_costs.b_i};
+
+    let mut report = SolutionReport::new(response_row, multiple_optima, statistics);
+    report.alternative_optima = alternative_optima;
+    report.vertices = vertices;
+
+    LinearProgramResponse::LinearProgram(report)
+}
+
+// a problem to solve with what the cache already had for it, looked up first so the X-Cache header can go out before
+// the answer is known
+pub struct SolveRequest
+{
+    pub linear_program: row_arithmetic::LinearProgram,
+    pub options: SolveOptions,
+    key: cache::CacheKey,
+    cached: Option<LinearProgramResponse>
+}
+
+impl SolveRequest
+{
+    pub fn new(linear_program: row_arithmetic::LinearProgram, options: SolveOptions, cache: &cache::ResultCache) -> Self
+    {
+        let key = cache::CacheKey::new(&linear_program, &options);
+        let cached = cache.get(&key);
+        SolveRequest { linear_program: linear_program, options: options, key: key, cached: cached }
+    }
+
+    pub fn hit(&self) -> bool
+    {
+        self.cached.is_some()
+    }
+}
+
+// serves the response from the cache when it can, keeps the run in the store if it is saving solves and counts the
+// solve against the caller's key
+pub async fn solve_and_record(request: SolveRequest, tag: &str, caller: &auth::Caller, store: &store::Store, cache: &cache::ResultCache) -> LinearProgramResponse
+{
+    let SolveRequest { linear_program, options, key, cached } = request;
+    let problem = if store.save_solves { Some(linear_program.clone()) } else { None };
+
+    // an answer from the cache was not solved for this caller, only the request counts against the key
+    let response = match cached
+    {
+        Some(response) => response,
+        None =>
+        {
+            let span = tracing::Span::current();
+            match rocket::tokio::task::spawn_blocking(move || span.in_scope(|| timed_solve(linear_program, &options))).await
+            {
+                Ok((response, wall_time)) =>
+                {
+                    caller.record(&response, wall_time);
+                    cache.insert(key, &response);
+                    response
+                },
+                Err(error) => LinearProgramResponse::Error(error::SimplexError::internal(format!("The solver stopped unexpectedly: {}", error)))
+            }
+        }
+    };
+
+    // losing the record of a run should not cost the client its answer
+    if let Some(problem) = problem
+    {
+        if let Err(error) = store.save(problem, Some(&response), vec![String::from(tag)], caller.name())
+        {
+            tracing::error!(%error, "could not save the run");
+        }
+    }
+
+    response
+}
+
+// the wall clock time the solve took, which is what the usage of a key is counted in
+pub fn timed_solve(linear_program: row_arithmetic::LinearProgram, options: &SolveOptions) -> (LinearProgramResponse, std::time::Duration)
+{
+    let start = Instant::now();
+    let response = solve_linear_program(linear_program, options);
+    (response, start.elapsed())
+}
+
+pub fn cache_header(hit: bool) -> Header<'static>
+{
+    Header::new("X-Cache", if hit { "hit" } else { "miss" })
+}
+
+// the X-Cache header says whether the response came from the result cache
+pub enum SolveResponse<'r>
+{
+    Streamed(JsonStream<'r>, Header<'static>),
+    Refused(Json<LinearProgramResponse>, Header<'static>)
+}
+
+impl<'r> Responder<'r, 'r> for SolveResponse<'r>
+{
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'r>
+    {
+        let (response, cache) = match self
+        {
+            SolveResponse::Streamed(stream, cache) => (stream.respond_to(request)?, cache),
+            SolveResponse::Refused(json, cache) => (json.respond_to(request)?, cache)
+        };
+
+        Response::build_from(response).header(cache).ok()
+    }
+}
+
+// the answer is streamed so that the solve stops once the client has gone away
+#[post("/?<solve..>", data = "<linear_program>")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0))]
+async fn index<'r>(linear_program: parse::JsonBody<row_arithmetic::LinearProgram>, solve: SolveQuery, request_id: logging::RequestId, caller: auth::Caller,
+    solver_config: &State<config::SolverConfig>, store: &'r State<store::Store>, cache: &'r State<cache::ResultCache>) -> SolveResponse<'r>
+{
+    if let Err(error) = caller.check(&linear_program)
+    {
+        return SolveResponse::Refused(Json(LinearProgramResponse::Error(error)), cache_header(false));
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let options = solve.options(solver_config, Some(cancelled.clone()));
+
+    let request = SolveRequest::new(linear_program.into_inner(), options, cache);
+    let header = cache_header(request.hit());
+
+    // the stream is polled after this handler returned, outside of its span
+    let span = tracing::Span::current();
+    let answer = async move { solve_and_record(request, "index", &caller, store, cache).await }.instrument(span);
+
+    SolveResponse::Streamed(stream_answer(cancelled, answer), header)
+}
+
+#[derive(Responder)]
+pub enum RenderResponse
+{
+    Html(RawHtml<String>),
+    Latex((ContentType, String)),
+    Error(status::Custom<Json<LinearProgramResponse>>)
+}
+
+// picks the acceptable format with the highest weight, anything that accepts text falls back to html
+fn negotiate_render_format(accept: Option<&Accept>) -> Option<render::RenderFormat>
+{
+    let accept = match accept
+    {
+        Some(accept) => accept,
+        None => return Some(render::RenderFormat::Html)
+    };
+
+    let mut media_types: Vec<_> = accept.iter().collect();
+    media_types.sort_by(|x, y| y.weight_or(1.0).partial_cmp(&x.weight_or(1.0)).unwrap_or(std::cmp::Ordering::Equal));
+
+    for media_type in media_types.iter().map(|x| x.media_type())
+    {
+        if media_type.sub() == "x-latex" || media_type.sub() == "x-tex"
+        {
+            return Some(render::RenderFormat::Latex);
+        }
+
+        if media_type.sub() == "html" || media_type.sub() == "*"
+        {
+            return Some(render::RenderFormat::Html);
+        }
+    }
+
+    None
+}
+
+// the trace is a full solve, which runs on a blocking thread like every other solve
+#[post("/render?<trace>", data = "<linear_program>")]
+async fn render_tableau(linear_program: parse::JsonBody<row_arithmetic::LinearProgram>, accept: Option<&Accept>, trace: Option<bool>, caller: auth::Caller,
+    solver_config: &State<config::SolverConfig>) -> RenderResponse
+{
+    let format = match negotiate_render_format(accept)
+    {
+        Some(format) => format,
+        None => return RenderResponse::Error(status::Custom(Status::NotAcceptable, 
+            Json(LinearProgramResponse::Error(error::SimplexError::new(error::ErrorCode::NotAcceptable, format!("Tableaus can only be rendered as text/html or application/x-latex"))))))
+    };
+
+    match caller.check(&linear_program).and_then(|_| row_arithmetic::perform_checks(&linear_program))
+    {
+        Ok(_) => (),
+        Err(error) => return RenderResponse::Error(status::Custom(v1::status_of(error.code), Json(LinearProgramResponse::Error(error))))
+    }
+
+    let mut linear_program = linear_program.into_inner();
+    let limits = solver_config.limits(None, None, None);
+
+    let rendered = rocket::tokio::task::spawn_blocking(move ||
+    {
+        linear_program.relative_costs = linear_program.calculate_costs();
+
+        if trace.unwrap_or(false)
+        {
+            let (_, steps) = linear_program.preform_simplex_with_trace(&limits);
+            render::render_trace(&steps, &format)
+        }
+        else
+        {
+            render::render_program(&linear_program, &format)
+        }
+    }).await;
+
+    let rendered = match rendered
+    {
+        Ok(rendered) => rendered,
+        Err(error) => return RenderResponse::Error(status::Custom(Status::InternalServerError,
+            Json(LinearProgramResponse::Error(error::SimplexError::internal(format!("The tableau could not be rendered: {}", error))))))
+    };
+
+    match format
+    {
+        render::RenderFormat::Html => RenderResponse::Html(RawHtml(rendered)),
+        render::RenderFormat::Latex => RenderResponse::Latex((ContentType::new("application", "x-latex"), rendered))
+    }
+}
+
+#[derive(Responder)]
+pub enum VisualizationResponse
+{
+    Svg((ContentType, String)),
+    Error(Json<LinearProgramResponse>)
+}
+
+// solving and finding the vertices both block, so they run on a blocking thread
+#[post("/visualize", data = "<linear_program>")]
+async fn visualize_feasible_region(linear_program: parse::JsonBody<row_arithmetic::LinearProgram>, caller: auth::Caller, solver_config: &State<config::SolverConfig>) -> VisualizationResponse
+{
+    match caller.check(&linear_program).and_then(|_| row_arithmetic::perform_checks(&linear_program))
+    {
+        Ok(_) => (),
+        Err(error) => return VisualizationResponse::Error(Json(LinearProgramResponse::Error(error)))
+    }
+
+    let linear_program = linear_program.into_inner();
+    let limits = solver_config.limits(None, None, None);
+
+    let drawn = rocket::tokio::task::spawn_blocking(move || visualize::feasible_region_svg(&linear_program, &limits)).await
+        .unwrap_or_else(|error| Err(error::SimplexError::internal(format!("The feasible region could not be drawn: {}", error))));
+
+    match drawn
+    {
+        Ok(svg) => VisualizationResponse::Svg((ContentType::SVG, svg)),
+        Err(error) => VisualizationResponse::Error(Json(LinearProgramResponse::Error(error)))
+    }
+}
+
+#[catch(400)]
+fn parsing_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    let error = error::guard_failure(request)
+        .unwrap_or_else(|| error::SimplexError::new(error::ErrorCode::InvalidJson, String::from("Failed to process JSON")));
+
+    Json(LinearProgramResponse::Error(error))
+}
+
+// json that parses but does not have the shape of a linear program
+#[catch(422)]
+fn unprocessable_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    parsing_error(request)
+}
+
+// a body or problem over one of the configured limits
+#[catch(413)]
+fn too_large_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    guard_error(request, error::ErrorCode::TooLarge, "The request body is larger than the server accepts")
+}
+
+// a missing or unknown API key, a key that may not do this or one over its rate limit
+#[catch(401)]
+fn unauthorized_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    guard_error(request, error::ErrorCode::Unauthorized, "An API key is needed")
+}
+
+#[catch(403)]
+fn forbidden_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    guard_error(request, error::ErrorCode::Forbidden, "The API key is not allowed to do this")
+}
+
+#[catch(429)]
+fn rate_limited_error(request: &rocket::Request) -> Json<LinearProgramResponse>
+{
+    guard_error(request, error::ErrorCode::RateLimited, "Too many requests")
+}
+
+fn guard_error(request: &rocket::Request, code: error::ErrorCode, message: &str) -> Json<LinearProgramResponse>
+{
+    let error = error::guard_failure(request).unwrap_or_else(|| error::SimplexError::new(code, String::from(message)));
+    Json(LinearProgramResponse::Error(error))
+}
+
+// the simplex binary, src/bin/simplex/main.rs runs it
+pub fn run_cli() -> std::process::ExitCode
+{
+    cli::run()
+}
+
+// installs the log subscriber for the [global.logging] table. only src/main.rs calls it, a rocket built by the tests or
+// by anything else that embeds the server leaves the subscriber to them
+pub fn init_logging(rocket: &rocket::Rocket<rocket::Build>)
+{
+    let logging_config: config::LoggingConfig = rocket.figment().extract_inner("logging").unwrap_or_default();
+    logging::init(&logging_config);
+}
+
+// the server, src/main.rs launches it
+pub fn rocket() -> rocket::Rocket<rocket::Build>
+{
+    let rocket = rocket::build();
+    let logging_config: config::LoggingConfig = rocket.figment().extract_inner("logging").unwrap_or_default();
+
+    // Rocket's own log lines are not json, with json logs it only keeps the ones about failing to launch
+    let rocket = if logging_config.format == "json"
+    {
+        let figment = rocket.figment().clone().merge(("log_level", rocket::config::LogLevel::Critical));
+        rocket.configure(figment)
+    }
+    else
+    {
+        rocket
+    };
+
+    let solver_config: config::SolverConfig = rocket.figment().extract_inner("solver").unwrap_or_default();
+    let jobs_config: config::JobsConfig = rocket.figment().extract_inner("jobs").unwrap_or_default();
+    let batch_config: config::BatchConfig = rocket.figment().extract_inner("batch").unwrap_or_default();
+    let cors_config: config::CorsConfig = rocket.figment().extract_inner("cors").unwrap_or_default();
+    let problem_limits: config::ProblemLimitsConfig = rocket.figment().extract_inner("problem_limits").unwrap_or_default();
+
+    rocket
+        .manage(solver_config)
+        .manage(jobs::JobQueue::new(&jobs_config))
+        .manage(batch_config)
+        .manage(problem_limits)
+        .mount("/", routes![index, hello_world, render_tableau, visualize_feasible_region])
+        .mount("/", jobs::routes())
+        .mount("/", streaming::routes())
+        .mount("/", batch::routes())
+        .mount("/", store::routes())
+        .mount("/", cache::routes())
+        .mount("/", validate::routes())
+        .mount("/", openapi::routes())
+        .mount("/", cors::routes())
+        .mount("/", auth::routes())
+        .mount("/", metrics::routes())
+        .mount("/", health::routes())
+        // under /v1 every error is a SimplexError with the status of its code, the routes mounted at both answer theirs
+        // through v1::RouteError
+        .mount("/v1", v1::routes())
+        .mount("/v1", jobs::routes())
+        .mount("/v1", streaming::routes())
+        .mount("/v1", batch::routes())
+        .mount("/v1", store::routes())
+        .mount("/v1", cache::routes())
+        .mount("/v1", validate::routes())
+        .mount("/v1", auth::routes())
+        .register("/", catchers![parsing_error, unprocessable_error, too_large_error, unauthorized_error, forbidden_error, rate_limited_error])
+        .register("/v1", v1::catchers())
+        .attach(AdHoc::try_on_ignite("CORS", |rocket| async
+        {
+            match cors::Cors::new(cors_config)
+            {
+                Ok(cors) => Ok(rocket.attach(cors)),
+                Err(error) =>
+                {
+                    tracing::error!(%error, "could not set up CORS");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(openapi::fairing())
+        .attach(metrics::RequestMetrics)
+        .attach(logging::RequestLogger)
+        .attach(shutdown::fairing())
+        .attach(AdHoc::try_on_ignite("Run store", |rocket| async
+        {
+            let store_config: config::StoreConfig = rocket.figment().extract_inner("store").unwrap_or_default();
+
+            match store::Store::open(&store_config)
+            {
+                Ok(store) => Ok(rocket.manage(store)),
+                Err(error) => 
+                {
+                    tracing::error!(%error, "could not open the store");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::try_on_ignite("API keys", |rocket| async
+        {
+            let auth_config: config::AuthConfig = rocket.figment().extract_inner("auth").unwrap_or_default();
+
+            match auth::ApiKeys::load(&auth_config, rocket.state::<store::Store>())
+            {
+                Ok(api_keys) => Ok(rocket.manage(api_keys)),
+                Err(error) =>
+                {
+                    tracing::error!(%error, "could not load the API keys");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::try_on_ignite("Queued jobs", |rocket| async
+        {
+            let jobs_config: config::JobsConfig = rocket.figment().extract_inner("jobs").unwrap_or_default();
+
+            let restored = match (rocket.state::<jobs::JobQueue>(), rocket.state::<store::Store>(), rocket.state::<auth::ApiKeys>())
+            {
+                (Some(job_queue), Some(store), Some(api_keys)) if jobs_config.persist => store.tree("jobs").and_then(|tree| job_queue.restore(&tree, api_keys)),
+                _ => Ok(0)
+            };
+
+            match restored
+            {
+                Ok(0) => Ok(rocket),
+                Ok(restored) =>
+                {
+                    tracing::info!(jobs = restored, "queued the jobs kept from the last shutdown again");
+                    Ok(rocket)
+                },
+                Err(error) =>
+                {
+                    tracing::error!(%error, "could not queue the kept jobs again");
+                    Err(rocket)
+                }
+            }
+        }))
+        .attach(AdHoc::try_on_ignite("Result cache", |rocket| async
+        {
+            let cache_config: config::CacheConfig = rocket.figment().extract_inner("cache").unwrap_or_default();
+
+            let tree = match rocket.state::<store::Store>()
+            {
+                Some(store) if cache_config.persist => match store.tree("cache")
+                {
+                    Ok(tree) => Some(tree),
+                    Err(error) =>
+                    {
+                        tracing::error!(%error, "could not open the result cache");
+                        return Err(rocket);
+                    }
+                },
+                _ => None
+            };
+
+            let cache = cache::ResultCache::new(&cache_config, tree);
+            Ok(rocket.manage(cache))
+        }))
+    
+}
\ No newline at end of file
@@ -0,0 +1,270 @@
+use std::cell::RefCell;
+use std::fmt;
+
+use rocket::serde::{Deserialize, Deserializer};
+use rocket::serde::de::{self, IgnoredAny, SeqAccess, Visitor};
+
+use crate::config::ProblemLimitsConfig;
+use crate::error::{ErrorCode, SimplexError};
+use crate::row_arithmetic::Row;
+
+// the limits of the body being parsed on this thread, None outside of parse_with_limits so problems read back from the
+// store or a keys file are never limited
+struct ActiveLimits
+{
+    limits: ProblemLimitsConfig,
+    // only a batch is counted against it
+    max_problems: Option<usize>,
+    nonzeros: usize,
+    exceeded: Option<SimplexError>
+}
+
+thread_local!
+{
+    static ACTIVE: RefCell<Option<ActiveLimits>> = const { RefCell::new(None) };
+}
+
+// runs the parse with the limits counted while the body is read, so a tableau over a limit is turned away at the
+// entry that crossed it instead of after all of it is in memory. the second value is the limit that was exceeded
+pub fn parse_with_limits<T>(limits: &ProblemLimitsConfig, max_problems: Option<usize>, parse: impl FnOnce() -> T) -> (T, Option<SimplexError>)
+{
+    ACTIVE.with(|active| *active.borrow_mut() = Some(ActiveLimits { limits: limits.clone(), max_problems: max_problems, nonzeros: 0, exceeded: None }));
+    let parsed = parse();
+    let exceeded = ACTIVE.with(|active| active.borrow_mut().take()).and_then(|active| active.exceeded);
+
+    (parsed, exceeded)
+}
+
+fn limits() -> Option<ProblemLimitsConfig>
+{
+    ACTIVE.with(|active| active.borrow().as_ref().map(|active| active.limits.clone()))
+}
+
+// remembers the first limit that was exceeded, serde only carries the message up to parse_json
+fn exceed<E: de::Error>(field: Option<&str>, limit: &str, maximum: usize, found: Option<usize>, message: String) -> E
+{
+    let mut error = SimplexError::new(ErrorCode::TooLarge, message).with_limit(limit);
+    if let Some(field) = field
+    {
+        error = error.with_field(field);
+    }
+    error.details.expected = Some(maximum);
+    error.details.found = found;
+
+    ACTIVE.with(|active| if let Some(active) = active.borrow_mut().as_mut()
+    {
+        active.exceeded.get_or_insert(error.clone());
+    });
+
+    E::custom(error.message)
+}
+
+struct RowsVisitor;
+
+impl<'de> Visitor<'de> for RowsVisitor
+{
+    type Value = Vec<Row>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a list of rows")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<Vec<Row>, A::Error>
+    {
+        let limits = limits();
+        let mut rows: Vec<Row> = Vec::new();
+
+        while let Some(row) = sequence.next_element::<Row>()?
+        {
+            rows.push(row);
+
+            match &limits
+            {
+                Some(limits) if rows.len() > limits.max_rows =>
+                {
+                    let found = rows.len() + skip_rest(&mut sequence)?;
+                    return Err(exceed(Some("tableau"), "max_rows", limits.max_rows, Some(found),
+                        format!("The tableau has {} rows, more than the {} set by problem_limits.max_rows", found, limits.max_rows)));
+                },
+                _ => ()
+            };
+        }
+
+        Ok(rows)
+    }
+}
+
+// the entries past a limit are only counted so the error can say how many there were, nothing is kept of them
+fn skip_rest<'de, A: SeqAccess<'de>>(sequence: &mut A) -> Result<usize, A::Error>
+{
+    let mut skipped = 0;
+    while sequence.next_element::<IgnoredAny>()?.is_some()
+    {
+        skipped += 1;
+    }
+
+    Ok(skipped)
+}
+
+// the rows of the tableau count their non-zero entries, the costs and the solution only their length. b has one entry
+// per row so it is held to max_rows instead of max_columns
+struct EntriesVisitor
+{
+    field: &'static str,
+    count_nonzeros: bool
+}
+
+impl<'de> Visitor<'de> for EntriesVisitor
+{
+    type Value = Vec<f32>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a list of numbers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<Vec<f32>, A::Error>
+    {
+        let limits = limits();
+        let mut entries: Vec<f32> = Vec::new();
+
+        while let Some(entry) = sequence.next_element::<f32>()?
+        {
+            entries.push(entry);
+
+            let limits = match &limits
+            {
+                Some(limits) => limits,
+                None => continue
+            };
+
+            let (limit, maximum) = match self.field
+            {
+                "b" => ("max_rows", limits.max_rows),
+                _ => ("max_columns", limits.max_columns)
+            };
+
+            if entries.len() > maximum
+            {
+                let found = entries.len() + skip_rest(&mut sequence)?;
+                let message = match self.field
+                {
+                    "tableau" => format!("A row has {} columns, more than the {} set by problem_limits.max_columns", found, maximum),
+                    "b" => format!("The b list has {} entries, more than the {} rows set by problem_limits.max_rows", found, maximum),
+                    field => format!("The {} list has {} entries, more than the {} columns set by problem_limits.max_columns", field, found, maximum)
+                };
+                return Err(exceed(Some(self.field), limit, maximum, Some(found), message));
+            }
+
+            if self.count_nonzeros && entry != 0.0
+            {
+                let nonzeros = ACTIVE.with(|active| active.borrow_mut().as_mut().map_or(0, |active| { active.nonzeros += 1; active.nonzeros }));
+                if nonzeros > limits.max_nonzeros
+                {
+                    // how many there are in all would take reading the rest of the body, so found is left out
+                    return Err(exceed(Some("tableau"), "max_nonzeros", limits.max_nonzeros, None,
+                        format!("The rows have more than {} non-zero entries, the limit set by problem_limits.max_nonzeros", limits.max_nonzeros)));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+pub fn deserialize_rows<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Row>, D::Error>
+{
+    deserializer.deserialize_seq(RowsVisitor)
+}
+
+pub fn deserialize_entries<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error>
+{
+    deserializer.deserialize_seq(EntriesVisitor { field: "tableau", count_nonzeros: true })
+}
+
+pub fn deserialize_costs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error>
+{
+    deserializer.deserialize_seq(EntriesVisitor { field: "costs", count_nonzeros: false })
+}
+
+pub fn deserialize_solution<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error>
+{
+    deserializer.deserialize_seq(EntriesVisitor { field: "solution", count_nonzeros: false })
+}
+
+// the lists a scenario override may leave out, null or missing keeps the one of the base problem
+struct OptionalEntriesVisitor(EntriesVisitor);
+
+impl<'de> Visitor<'de> for OptionalEntriesVisitor
+{
+    type Value = Option<Vec<f32>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        formatter.write_str("a list of numbers or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Option<Vec<f32>>, E>
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Option<Vec<f32>>, E>
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+    {
+        deserializer.deserialize_seq(self.0).map(Some)
+    }
+}
+
+pub fn deserialize_override_costs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+{
+    deserializer.deserialize_option(OptionalEntriesVisitor(EntriesVisitor { field: "costs", count_nonzeros: false }))
+}
+
+pub fn deserialize_override_b<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+{
+    deserializer.deserialize_option(OptionalEntriesVisitor(EntriesVisitor { field: "b", count_nonzeros: false }))
+}
+
+// the problems or scenarios of a batch, counted against batch.max_problems while they are read. the ones past the limit
+// are skipped instead of kept so the error can say how many there were
+pub fn visit_problems<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(mut sequence: A) -> Result<Vec<T>, A::Error>
+{
+    let max_problems = ACTIVE.with(|active| active.borrow().as_ref().and_then(|active| active.max_problems));
+    let mut problems: Vec<T> = Vec::new();
+
+    loop
+    {
+        // max_nonzeros is a limit on each problem, not on the batch
+        ACTIVE.with(|active| if let Some(active) = active.borrow_mut().as_mut()
+        {
+            active.nonzeros = 0;
+        });
+
+        let problem = match sequence.next_element::<T>()?
+        {
+            Some(problem) => problem,
+            None => break
+        };
+        problems.push(problem);
+
+        match max_problems
+        {
+            Some(maximum) if problems.len() > maximum =>
+            {
+                let found = problems.len() + skip_rest(&mut sequence)?;
+
+                return Err(exceed(None, "max_problems", maximum, Some(found),
+                    format!("The batch has too many problems: problems = {}, maximum = {}", found, maximum)));
+            },
+            _ => ()
+        };
+    }
+
+    Ok(problems)
+}
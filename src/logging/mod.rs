@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::{Data, Request, Response};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+use crate::cache::fnv_1a;
+use crate::config::LoggingConfig;
+
+// a subscriber that is already installed is kept
+pub fn init(config: &LoggingConfig)
+{
+    let filter = match EnvFilter::try_new(&config.level)
+    {
+        Ok(filter) => filter,
+        Err(error) =>
+        {
+            eprintln!("The log level {} could not be read, logging at info instead: {}", config.level, error);
+            EnvFilter::new("info")
+        }
+    };
+
+    // closing a span logs how long it was busy, which is where the time of a request or a solve shows up
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_span_events(FmtSpan::CLOSE);
+
+    let _ = match config.format.as_str()
+    {
+        "json" => builder.json().with_current_span(true).with_span_list(true).try_init(),
+        _ => builder.try_init()
+    };
+}
+
+struct RequestContext
+{
+    id: String,
+    started: Instant
+}
+
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+// a request id sent by a proxy in front of us is kept so the logs of both can be matched up
+fn context<'r>(request: &'r Request<'_>) -> &'r RequestContext
+{
+    request.local_cache(||
+    {
+        let sent = request.headers().get_one("X-Request-Id")
+            .filter(|id| !id.is_empty() && id.len() <= 64 && id.chars().all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_'));
+
+        let id = match sent
+        {
+            Some(id) => String::from(id),
+            None =>
+            {
+                let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_nanos()).unwrap_or(0);
+                let count = REQUESTS.fetch_add(1, Ordering::Relaxed);
+                format!("{:016x}", fnv_1a(&[nanos.to_le_bytes().as_slice(), count.to_le_bytes().as_slice()].concat()))
+            }
+        };
+
+        RequestContext { id: id, started: Instant::now() }
+    })
+}
+
+// the id the request is logged under, also sent back in the X-Request-Id header
+pub struct RequestId(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId
+{
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, ()>
+    {
+        Outcome::Success(RequestId(context(request).id.clone()))
+    }
+}
+
+pub struct RequestLogger;
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger
+{
+    fn info(&self) -> Info
+    {
+        Info { name: "Log every request with its id", kind: Kind::Request | Kind::Response }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>)
+    {
+        context(request);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>)
+    {
+        let context = context(request);
+        let route = request.route().map_or(String::from("unmatched"), |route| route.uri.path().to_string());
+        let status = response.status().code;
+
+        let elapsed_ms = context.started.elapsed().as_secs_f64() * 1000.0;
+        if status >= 500
+        {
+            tracing::error!(request_id = %context.id, method = %request.method(), path = %request.uri().path(), route = %route, status, elapsed_ms, "request failed");
+        }
+        else
+        {
+            tracing::info!(request_id = %context.id, method = %request.method(), path = %request.uri().path(), route = %route, status, elapsed_ms, "request");
+        }
+
+        response.set_header(Header::new("X-Request-Id", context.id.clone()));
+    }
+}
@@ -0,0 +1,516 @@
+use std::fmt::Write;
+
+use crate::model::{Model, Relation, Sense};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token
+{
+    Number(f64),
+    Name(String),
+    Relation(Relation),
+    Plus,
+    Minus,
+    Colon
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section
+{
+    None,
+    Objective,
+    Constraints,
+    Bounds,
+    // general and integer variables are read as continuous ones
+    Integers,
+    Binaries
+}
+
+// the keywords a section starts with, longest first so "subject to" is not taken for "st"
+const SECTIONS: [(&str, Section); 21] = [
+    ("subject to", Section::Constraints), ("such that", Section::Constraints), ("s.t.", Section::Constraints), ("st.", Section::Constraints),
+    ("st", Section::Constraints), ("maximize", Section::Objective), ("maximise", Section::Objective), ("maximum", Section::Objective),
+    ("max", Section::Objective), ("minimize", Section::Objective), ("minimise", Section::Objective), ("minimum", Section::Objective),
+    ("min", Section::Objective), ("bounds", Section::Bounds), ("bound", Section::Bounds), ("generals", Section::Integers),
+    ("general", Section::Integers), ("gen", Section::Integers), ("integers", Section::Integers), ("binaries", Section::Binaries),
+    ("binary", Section::Binaries)];
+
+fn is_name_character(character: char) -> bool
+{
+    character.is_alphanumeric() || "!\"#$%&()/,.;?@_`'{}|~".contains(character)
+}
+
+fn tokenize(line: usize, text: &str, tokens: &mut Vec<(usize, Token)>) -> Result<(), String>
+{
+    let characters: Vec<char> = text.chars().collect();
+    let mut position = 0;
+
+    while position < characters.len()
+    {
+        let character = characters[position];
+        let next = characters.get(position + 1).copied();
+
+        let (token, length) = match character
+        {
+            _ if character.is_whitespace() => { position += 1; continue; },
+            '+' => (Token::Plus, 1),
+            '-' => (Token::Minus, 1),
+            ':' => (Token::Colon, 1),
+            '<' | '>' | '=' =>
+            {
+                let relation = match (character, next)
+                {
+                    ('<', _) | ('=', Some('<')) => Relation::LessEqual,
+                    ('>', _) | ('=', Some('>')) => Relation::GreaterEqual,
+                    _ => Relation::Equal
+                };
+                let length = if matches!(next, Some('=') | Some('<') | Some('>')) { 2 } else { 1 };
+                (Token::Relation(relation), length)
+            },
+            _ if character.is_ascii_digit() || character == '.' =>
+            {
+                let mut end = position;
+                while end < characters.len() && (characters[end].is_ascii_digit() || characters[end] == '.'
+                    || ((characters[end] == 'e' || characters[end] == 'E') && characters.get(end + 1).map_or(false, |x| x.is_ascii_digit() || *x == '-' || *x == '+'))
+                    || ((characters[end] == '-' || characters[end] == '+') && end > position && (characters[end - 1] == 'e' || characters[end - 1] == 'E')))
+                {
+                    end += 1;
+                }
+
+                let number: String = characters[position..end].iter().collect();
+                match number.parse::<f64>()
+                {
+                    Ok(value) => (Token::Number(value), end - position),
+                    Err(_) => return Err(format!("line {}: {} is not a number", line, number))
+                }
+            },
+            _ if is_name_character(character) =>
+            {
+                let mut end = position;
+                while end < characters.len() && is_name_character(characters[end])
+                {
+                    end += 1;
+                }
+                (Token::Name(characters[position..end].iter().collect()), end - position)
+            },
+            other => return Err(format!("line {}: {} cannot start a name", line, other))
+        };
+
+        tokens.push((line, token));
+        position += length;
+    }
+
+    Ok(())
+}
+
+// the keyword a line starts with, if any, and what follows it
+fn section_of(line: &str) -> Option<(Section, &str)>
+{
+    let lowered = line.to_lowercase();
+
+    if lowered.trim() == "end"
+    {
+        return Some((Section::None, ""));
+    }
+
+    for (keyword, section) in SECTIONS.iter()
+    {
+        if lowered.starts_with(keyword)
+        {
+            let rest = &line[keyword.len()..];
+            // "st" must not take "stock: ..." for a section
+            if rest.is_empty() || rest.starts_with(char::is_whitespace)
+            {
+                return Some((*section, rest));
+            }
+        }
+    }
+
+    None
+}
+
+struct Parser
+{
+    tokens: Vec<(usize, Token)>,
+    position: usize
+}
+
+impl Parser
+{
+    fn peek(&self) -> Option<&Token>
+    {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token>
+    {
+        self.tokens.get(self.position + offset).map(|(_, token)| token)
+    }
+
+    fn line(&self) -> usize
+    {
+        self.tokens.get(self.position).or(self.tokens.last()).map_or(0, |(line, _)| *line)
+    }
+
+    fn next(&mut self) -> Option<Token>
+    {
+        let token = self.tokens.get(self.position).map(|(_, token)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn done(&self) -> bool
+    {
+        self.position >= self.tokens.len()
+    }
+
+    // "name:" in front of an objective or a constraint
+    fn label(&mut self) -> Option<String>
+    {
+        match (self.peek(), self.peek_at(1))
+        {
+            (Some(Token::Name(name)), Some(Token::Colon)) =>
+            {
+                let name = name.clone();
+                self.position += 2;
+                Some(name)
+            },
+            _ => None
+        }
+    }
+
+    // terms up to a relation or the end of the section, and the constants among them
+    fn expression(&mut self, model: &mut Model) -> Result<(Vec<(usize, f64)>, f64), String>
+    {
+        let mut terms: Vec<(usize, f64)> = Vec::new();
+        let mut constant = 0.0;
+
+        loop
+        {
+            let mut sign = 1.0;
+            let mut seen_sign = false;
+
+            while let Some(Token::Plus) | Some(Token::Minus) = self.peek()
+            {
+                if self.next() == Some(Token::Minus) { sign = -sign; }
+                seen_sign = true;
+            }
+
+            let coefficient = match self.peek()
+            {
+                Some(Token::Number(value)) =>
+                {
+                    let value = *value;
+                    self.position += 1;
+                    Some(value)
+                },
+                _ => None
+            };
+
+            // a name followed by a colon is the label of the next constraint
+            match (self.peek(), self.peek_at(1))
+            {
+                (Some(Token::Name(name)), next) if next != Some(&Token::Colon) =>
+                {
+                    let name = name.clone();
+                    self.position += 1;
+                    terms.push((model.variable(&name), sign * coefficient.unwrap_or(1.0)));
+                },
+                _ if coefficient.is_some() => constant += sign * coefficient.unwrap_or(0.0),
+                _ if seen_sign => return Err(format!("line {}: a sign with no term after it", self.line())),
+                _ => return Ok((terms, constant))
+            };
+        }
+    }
+
+    fn signed_number(&mut self) -> Result<f64, String>
+    {
+        let mut sign = 1.0;
+        while let Some(Token::Plus) | Some(Token::Minus) = self.peek()
+        {
+            if self.next() == Some(Token::Minus) { sign = -sign; }
+        }
+
+        let line = self.line();
+        match self.next()
+        {
+            Some(Token::Number(value)) => Ok(sign * value),
+            Some(Token::Name(name)) if name.eq_ignore_ascii_case("inf") || name.eq_ignore_ascii_case("infinity") => Ok(sign * f64::INFINITY),
+            _ => Err(format!("line {}: expected a number", line))
+        }
+    }
+
+    fn relation(&mut self) -> Result<Relation, String>
+    {
+        let line = self.line();
+        match self.next()
+        {
+            Some(Token::Relation(relation)) => Ok(relation),
+            _ => Err(format!("line {}: expected <=, >= or =", line))
+        }
+    }
+}
+
+// the CPLEX LP format, without quadratic terms, semi-continuous variables or special ordered sets
+pub fn read(text: &str) -> Result<Model, String>
+{
+    let mut model = Model::new("model");
+    let mut sections: Vec<(Section, Vec<(usize, Token)>)> = Vec::new();
+    let mut current = Section::None;
+    let mut tokens: Vec<(usize, Token)> = Vec::new();
+
+    for (index, raw) in text.lines().enumerate()
+    {
+        let line = index + 1;
+
+        // the comment the writer puts the name of the model in
+        if let Some(name) = raw.trim_start().strip_prefix("\\Problem name:")
+        {
+            model.name = String::from(name.trim());
+        }
+        let content = raw.split('\\').next().unwrap_or("");
+
+        let content = match section_of(content.trim_start())
+        {
+            Some((section, rest)) =>
+            {
+                if current == Section::Objective && section == Section::Objective
+                {
+                    return Err(format!("line {}: the model has a second objective", line));
+                }
+
+                sections.push((current, std::mem::take(&mut tokens)));
+                current = section;
+
+                if section == Section::Objective
+                {
+                    let lowered = content.trim_start().to_lowercase();
+                    model.sense = if lowered.starts_with("max") { Sense::Maximize } else { Sense::Minimize };
+                }
+
+                if lowered_is_end(content)
+                {
+                    break;
+                }
+                rest
+            },
+            None => content
+        };
+
+        tokenize(line, content, &mut tokens)?;
+    }
+    sections.push((current, tokens));
+
+    for (section, tokens) in sections.into_iter()
+    {
+        let mut parser = Parser { tokens: tokens, position: 0 };
+
+        match section
+        {
+            Section::None if parser.done() => (),
+            Section::None => return Err(format!("line {}: expected Maximize or Minimize first", parser.line())),
+            Section::Objective =>
+            {
+                if let Some(name) = parser.label()
+                {
+                    model.objective_name = name;
+                }
+
+                // a constant in the objective moves every solution the same, it is dropped
+                for (column, value) in parser.expression(&mut model)?.0
+                {
+                    model.objective[column] += value;
+                }
+
+                if !parser.done()
+                {
+                    return Err(format!("line {}: the objective cannot have a relation", parser.line()));
+                }
+            },
+            Section::Constraints =>
+            {
+                while !parser.done()
+                {
+                    let name = parser.label().unwrap_or(format!("c{}", model.constraints.len() + 1));
+                    let (terms, constant) = parser.expression(&mut model)?;
+                    let relation = parser.relation()?;
+                    let rhs = parser.signed_number()?;
+                    model.add_constraint(&name, &terms, relation, rhs - constant);
+                }
+            },
+            Section::Bounds =>
+            {
+                while !parser.done()
+                {
+                    read_bound(&mut parser, &mut model)?;
+                }
+            },
+            Section::Integers | Section::Binaries =>
+            {
+                while let Some(token) = parser.next()
+                {
+                    let name = match token
+                    {
+                        Token::Name(name) => name,
+                        _ => return Err(format!("line {}: expected the name of a variable", parser.line()))
+                    };
+
+                    let column = model.variable(&name);
+                    if section == Section::Binaries
+                    {
+                        model.variables[column].lower = 0.0;
+                        model.variables[column].upper = 1.0;
+                    }
+                }
+            }
+        };
+    }
+
+    Ok(model)
+}
+
+fn lowered_is_end(content: &str) -> bool
+{
+    content.trim().eq_ignore_ascii_case("end")
+}
+
+// x free, x <= 4, 2 <= x, -inf <= x <= 4 and x = 3
+fn read_bound(parser: &mut Parser, model: &mut Model) -> Result<(), String>
+{
+    let line = parser.line();
+
+    match (parser.peek().cloned(), parser.peek_at(1).cloned())
+    {
+        (Some(Token::Name(name)), Some(Token::Name(free))) if free.eq_ignore_ascii_case("free") =>
+        {
+            parser.position += 2;
+            let column = model.variable(&name);
+            model.variables[column].lower = f64::NEG_INFINITY;
+            model.variables[column].upper = f64::INFINITY;
+        },
+        (Some(Token::Name(name)), Some(Token::Relation(_))) if !name.eq_ignore_ascii_case("inf") && !name.eq_ignore_ascii_case("infinity") =>
+        {
+            parser.position += 1;
+            let relation = parser.relation()?;
+            let value = parser.signed_number()?;
+            set_bound(model, &name, relation, value);
+        },
+        _ =>
+        {
+            let value = parser.signed_number()?;
+            let relation = parser.relation()?;
+            let name = match parser.next()
+            {
+                Some(Token::Name(name)) => name,
+                _ => return Err(format!("line {}: expected the name of a variable", line))
+            };
+
+            // value <= x is x >= value
+            set_bound(model, &name, relation_flipped(relation), value);
+
+            if let Some(Token::Relation(_)) = parser.peek()
+            {
+                let relation = parser.relation()?;
+                let value = parser.signed_number()?;
+                set_bound(model, &name, relation, value);
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn relation_flipped(relation: Relation) -> Relation
+{
+    match relation
+    {
+        Relation::LessEqual => Relation::GreaterEqual,
+        Relation::GreaterEqual => Relation::LessEqual,
+        Relation::Equal => Relation::Equal
+    }
+}
+
+fn set_bound(model: &mut Model, name: &str, relation: Relation, value: f64)
+{
+    let column = model.variable(name);
+    let variable = &mut model.variables[column];
+
+    match relation
+    {
+        Relation::LessEqual => variable.upper = value,
+        Relation::GreaterEqual => variable.lower = value,
+        Relation::Equal =>
+        {
+            variable.lower = value;
+            variable.upper = value;
+        }
+    };
+}
+
+// at most this many terms go on one line
+const TERMS_PER_LINE: usize = 8;
+
+fn write_expression(text: &mut String, model: &Model, coefficients: &Vec<f64>)
+{
+    let terms: Vec<String> = coefficients.iter().enumerate().filter(|(_, x)| **x != 0.0).enumerate().map(|(index, (column, value))|
+    {
+        let sign = if *value < 0.0 { "-" } else if index > 0 { "+" } else { "" };
+        let magnitude = if value.abs() == 1.0 { String::new() } else { format!("{} ", value.abs()) };
+        format!("{} {}{}", sign, magnitude, model.variables[column].name).trim_start().to_string()
+    }).collect();
+
+    if terms.is_empty()
+    {
+        // LP files need a term, a 0 in front of the first variable writes nothing
+        let _ = write!(text, " 0 {}", model.variables.first().map_or("x", |variable| variable.name.as_str()));
+        return;
+    }
+
+    for (index, chunk) in terms.chunks(TERMS_PER_LINE).enumerate()
+    {
+        let _ = write!(text, "{}{}", if index > 0 { "\n  " } else { " " }, chunk.join(" "));
+    }
+}
+
+pub fn write(model: &Model) -> String
+{
+    let mut text = format!("\\Problem name: {}\n", model.name);
+
+    let _ = write!(text, "{}\n {}:", if model.sense == Sense::Maximize { "Maximize" } else { "Minimize" }, model.objective_name);
+    write_expression(&mut text, model, &model.objective);
+
+    let _ = writeln!(text, "\nSubject To");
+    for constraint in model.constraints.iter()
+    {
+        let relation = match constraint.relation
+        {
+            Relation::LessEqual => "<=",
+            Relation::GreaterEqual => ">=",
+            Relation::Equal => "="
+        };
+
+        let _ = write!(text, " {}:", constraint.name);
+        write_expression(&mut text, model, &constraint.coefficients);
+        let _ = writeln!(text, " {} {}", relation, constraint.rhs);
+    }
+
+    let bounds: Vec<String> = model.variables.iter().filter_map(|variable|
+    {
+        match (variable.lower, variable.upper)
+        {
+            (lower, upper) if lower == upper => Some(format!(" {} = {}", variable.name, lower)),
+            (lower, upper) if lower.is_infinite() && upper.is_infinite() => Some(format!(" {} free", variable.name)),
+            (lower, upper) if lower.is_infinite() => Some(format!(" -inf <= {} <= {}", variable.name, upper)),
+            (lower, upper) if upper.is_finite() => Some(format!(" {} <= {} <= {}", lower, variable.name, upper)),
+            (lower, _) if lower != 0.0 => Some(format!(" {} >= {}", variable.name, lower)),
+            _ => None
+        }
+    }).collect();
+
+    if !bounds.is_empty()
+    {
+        let _ = writeln!(text, "Bounds\n{}", bounds.join("\n"));
+    }
+
+    let _ = writeln!(text, "End");
+    text
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Duration;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+use rocket::{Request, Response, Route};
+
+use crate::row_arithmetic::LinearProgram;
+use crate::LinearProgramResponse;
+
+const LATENCY_BUCKETS: [f64; 12] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 2.5, 10.0];
+const ITERATION_BUCKETS: [f64; 10] = [0.0, 1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 1000.0, 10000.0];
+const SIZE_BUCKETS: [f64; 10] = [1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 1000.0, 10000.0, 100000.0];
+const NONZERO_BUCKETS: [f64; 9] = [1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0, 10000000.0, 100000000.0];
+
+// every name LinearProgramResponse::outcome gives
+const OUTCOMES: [&str; 5] = ["optimal", "unbounded", "infeasible", "limit_reached", "error"];
+
+pub struct Histogram
+{
+    buckets: &'static [f64],
+    // one per bucket and one more for +Inf, not cumulative, that is done when the text is written
+    counts: Vec<AtomicU64>,
+    // the sum is kept as the bits of an f64 so observing never takes a lock
+    sum: AtomicU64
+}
+
+impl Histogram
+{
+    pub fn new(buckets: &'static [f64]) -> Self
+    {
+        Histogram { buckets: buckets, counts: (0..=buckets.len()).map(|_| AtomicU64::new(0)).collect(), sum: AtomicU64::new(0f64.to_bits()) }
+    }
+
+    pub fn observe(&self, value: f64)
+    {
+        let bucket = self.buckets.iter().position(|upper| value <= *upper).unwrap_or(self.buckets.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+
+        let _ = self.sum.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some((f64::from_bits(bits) + value).to_bits()));
+    }
+
+    fn write(&self, text: &mut String, name: &str, help: &str)
+    {
+        let _ = writeln!(text, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+
+        let mut cumulative = 0;
+        for (index, count) in self.counts.iter().enumerate()
+        {
+            cumulative += count.load(Ordering::Relaxed);
+            let upper = self.buckets.get(index).map_or(String::from("+Inf"), |x| x.to_string());
+            let _ = writeln!(text, "{}_bucket{{le=\"{}\"}} {}", name, upper, cumulative);
+        }
+
+        let _ = writeln!(text, "{}_sum {}\n{}_count {}", name, f64::from_bits(self.sum.load(Ordering::Relaxed)), name, cumulative);
+    }
+}
+
+pub struct Metrics
+{
+    // by method, route and status, the route is the mounted pattern so /runs/1 and /runs/2 are one series
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    outcomes: [AtomicU64; 5],
+    solves_in_flight: AtomicI64,
+    solve_seconds: Histogram,
+    iterations: Histogram,
+    rows: Histogram,
+    columns: Histogram,
+    nonzeros: Histogram
+}
+
+impl Default for Metrics
+{
+    fn default() -> Self
+    {
+        Metrics::new()
+    }
+}
+
+impl Metrics
+{
+    pub fn new() -> Self
+    {
+        Metrics
+        {
+            requests: Mutex::new(BTreeMap::new()),
+            outcomes: Default::default(),
+            solves_in_flight: AtomicI64::new(0),
+            solve_seconds: Histogram::new(&LATENCY_BUCKETS),
+            iterations: Histogram::new(&ITERATION_BUCKETS),
+            rows: Histogram::new(&SIZE_BUCKETS),
+            columns: Histogram::new(&SIZE_BUCKETS),
+            nonzeros: Histogram::new(&NONZERO_BUCKETS)
+        }
+    }
+
+    pub fn record_request(&self, method: &str, route: &str, status: u16)
+    {
+        let mut requests = self.requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *requests.entry((String::from(method), String::from(route), status)).or_insert(0) += 1;
+    }
+
+    // counts the solve as in flight until the returned guard is dropped
+    pub fn start_solve(&self, linear_program: &LinearProgram) -> InFlight<'_>
+    {
+        self.rows.observe(linear_program.tableau.len() as f64);
+        self.columns.observe(linear_program.tableau.iter().map(|row| row.a_ij.len()).max().unwrap_or(0) as f64);
+        self.nonzeros.observe(linear_program.tableau.iter().map(|row| row.a_ij.iter().filter(|x| **x != 0.0).count()).sum::<usize>() as f64);
+
+        self.solves_in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(self)
+    }
+
+    pub fn finish_solve(&self, response: &LinearProgramResponse, elapsed: Duration)
+    {
+        let iterations = match response
+        {
+            LinearProgramResponse::LinearProgram(report) => Some(report.statistics.iterations),
+            LinearProgramResponse::LimitReached(report) => Some(report.statistics.iterations),
+            _ => None
+        };
+
+        if let Some(index) = OUTCOMES.iter().position(|x| *x == response.outcome())
+        {
+            self.outcomes[index].fetch_add(1, Ordering::Relaxed);
+        }
+
+        if let Some(iterations) = iterations
+        {
+            self.iterations.observe(iterations as f64);
+        }
+
+        self.solve_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn solves_in_flight(&self) -> i64
+    {
+        self.solves_in_flight.load(Ordering::Relaxed)
+    }
+
+    // the prometheus text exposition format, version 0.0.4
+    pub fn render(&self) -> String
+    {
+        let mut text = String::new();
+
+        let _ = writeln!(text, "# HELP simplex_http_requests_total Requests answered, by method, route and status\n# TYPE simplex_http_requests_total counter");
+        for ((method, route, status), count) in self.requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter()
+        {
+            let _ = writeln!(text, "simplex_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}", escape(method), escape(route), status, count);
+        }
+
+        let _ = writeln!(text, "# HELP simplex_solves_total Solves that ran the solver, by outcome\n# TYPE simplex_solves_total counter");
+        for (outcome, count) in OUTCOMES.iter().zip(self.outcomes.iter())
+        {
+            let _ = writeln!(text, "simplex_solves_total{{outcome=\"{}\"}} {}", outcome, count.load(Ordering::Relaxed));
+        }
+
+        let _ = writeln!(text, "# HELP simplex_solves_in_flight Solves running right now\n# TYPE simplex_solves_in_flight gauge");
+        let _ = writeln!(text, "simplex_solves_in_flight {}", self.solves_in_flight.load(Ordering::Relaxed));
+
+        self.solve_seconds.write(&mut text, "simplex_solve_duration_seconds", "Time a solve spent in the solver");
+        self.iterations.write(&mut text, "simplex_solve_iterations", "Pivots a solve took, for solves that got as far as pivoting");
+        self.rows.write(&mut text, "simplex_problem_rows", "Rows of the tableaus that were solved");
+        self.columns.write(&mut text, "simplex_problem_columns", "Columns of the tableaus that were solved");
+        self.nonzeros.write(&mut text, "simplex_problem_nonzeros", "Non-zero entries of the tableaus that were solved");
+
+        text
+    }
+}
+
+pub struct InFlight<'a>(&'a Metrics);
+
+impl Drop for InFlight<'_>
+{
+    fn drop(&mut self)
+    {
+        self.0.solves_in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn escape(value: &str) -> String
+{
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// every solve goes through solve_linear_program_observed, which has no Rocket state to reach, so the metrics of the
+// process live here
+pub fn global() -> &'static Metrics
+{
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics
+{
+    fn info(&self) -> Info
+    {
+        Info { name: "Count requests by route and status", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>)
+    {
+        // requests no route matched are kept in one series instead of one per path a scanner tried
+        let route = request.route().map_or(String::from("unmatched"), |route| route.uri.path().to_string());
+        global().record_request(request.method().as_str(), &route, response.status().code);
+    }
+}
+
+#[get("/metrics")]
+fn metrics() -> (ContentType, String)
+{
+    (ContentType::new("text", "plain").with_params(("version", "0.0.4")), global().render())
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![metrics]
+}
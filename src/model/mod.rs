@@ -0,0 +1,367 @@
+use crate::row_arithmetic::{LinearProgram, Row};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sense
+{
+    Minimize,
+    Maximize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relation
+{
+    LessEqual,
+    GreaterEqual,
+    Equal
+}
+
+impl Relation
+{
+    fn flipped(self) -> Self
+    {
+        match self
+        {
+            Relation::LessEqual => Relation::GreaterEqual,
+            Relation::GreaterEqual => Relation::LessEqual,
+            Relation::Equal => Relation::Equal
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint
+{
+    pub name: String,
+    // one per variable of the model
+    pub coefficients: Vec<f64>,
+    pub relation: Relation,
+    pub rhs: f64
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable
+{
+    pub name: String,
+    // -inf and inf when the variable is free on that side
+    pub lower: f64,
+    pub upper: f64
+}
+
+// a model the way MPS and LP files write it, which the solver only takes once it is in standard form
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model
+{
+    pub name: String,
+    pub sense: Sense,
+    pub objective_name: String,
+    pub objective: Vec<f64>,
+    pub variables: Vec<Variable>,
+    pub constraints: Vec<Constraint>
+}
+
+impl Model
+{
+    pub fn new(name: &str) -> Self
+    {
+        Model { name: String::from(name), sense: Sense::Minimize, objective_name: String::from("obj"), objective: Vec::new(), variables: Vec::new(), constraints: Vec::new() }
+    }
+
+    // the index of the variable, added with the default bounds of 0 and inf and no cost the first time it is named
+    pub fn variable(&mut self, name: &str) -> usize
+    {
+        if let Some(index) = self.variables.iter().position(|variable| variable.name == name)
+        {
+            return index;
+        }
+
+        self.variables.push(Variable { name: String::from(name), lower: 0.0, upper: f64::INFINITY });
+        self.objective.push(0.0);
+        for constraint in self.constraints.iter_mut()
+        {
+            constraint.coefficients.push(0.0);
+        }
+
+        self.variables.len() - 1
+    }
+
+    pub fn add_constraint(&mut self, name: &str, terms: &Vec<(usize, f64)>, relation: Relation, rhs: f64)
+    {
+        let mut coefficients = vec![0.0; self.variables.len()];
+        for (index, value) in terms.iter()
+        {
+            coefficients[*index] += value;
+        }
+
+        self.constraints.push(Constraint { name: String::from(name), coefficients: coefficients, relation: relation, rhs: rhs });
+    }
+}
+
+// the json linear program is already in standard form: Ax = b, x >= 0, costs maximized
+pub fn from_linear_program(linear_program: &LinearProgram, names: Option<&Vec<String>>) -> Model
+{
+    let mut model = Model::new("linear_program");
+    model.sense = Sense::Maximize;
+
+    for column in 0..linear_program.costs.len()
+    {
+        let name = names.and_then(|names| names.get(column)).cloned().unwrap_or(format!("x{}", column + 1));
+        model.variable(&name);
+        model.objective[column] = linear_program.costs[column] as f64;
+    }
+
+    for (index, row) in linear_program.tableau.iter().enumerate()
+    {
+        let terms: Vec<(usize, f64)> = row.a_ij.iter().enumerate().filter(|(_, x)| **x != 0.0).map(|(column, x)| (column, *x as f64)).collect();
+        model.add_constraint(&format!("r{}", index + 1), &terms, Relation::Equal, row.b_i as f64);
+    }
+
+    model
+}
+
+// the value of a variable of the model is shift + the sum of factor * column over its columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableMap
+{
+    pub name: String,
+    // its cost in the objective of the model
+    pub cost: f64,
+    pub shift: f64,
+    pub columns: Vec<(usize, f64)>
+}
+
+#[derive(Debug, Clone)]
+pub struct StandardForm
+{
+    pub linear_program: LinearProgram,
+    // the name of every column, slack_, surplus_ and artificial_ columns are the ones standard form added
+    pub columns: Vec<String>,
+    pub variables: Vec<VariableMap>,
+    pub sense: Sense,
+    // the objective of the model is the one of the standard form plus this, negated when the model is minimized
+    pub offset: f64
+}
+
+impl StandardForm
+{
+    // a json problem needs no changes, every column is a variable of its own
+    pub fn of(linear_program: LinearProgram) -> Self
+    {
+        let columns: Vec<String> = (0..linear_program.costs.len()).map(|column| format!("x{}", column + 1)).collect();
+        let variables = columns.iter().enumerate().map(|(column, name)| VariableMap { name: name.clone(), cost: linear_program.costs[column] as f64, shift: 0.0, columns: vec![(column, 1.0)] }).collect();
+
+        StandardForm { linear_program: linear_program, columns: columns, variables: variables, sense: Sense::Maximize, offset: 0.0 }
+    }
+
+    // the objective of the model at a solution of the solver, from the values rather than from the objective the solver
+    // reports, which is off by the offset and too large with big_m in it for an f32 to keep the digits
+    pub fn objective(&self, solution: &Vec<f32>) -> f64
+    {
+        self.variables.iter().zip(self.values(solution).iter()).map(|(variable, (_, value))| variable.cost * value).sum()
+    }
+
+    pub fn values(&self, solution: &Vec<f32>) -> Vec<(String, f64)>
+    {
+        self.variables.iter().map(|variable|
+        {
+            let value = variable.columns.iter().map(|(column, factor)| factor * solution.get(*column).copied().unwrap_or(0.0) as f64).sum::<f64>();
+            (variable.name.clone(), variable.shift + value)
+        }).collect()
+    }
+
+    // a positive artificial column means the model has no feasible point, or big_m was too small to drive it out
+    pub fn artificial_in_use(&self, solution: &Vec<f32>) -> bool
+    {
+        self.columns.iter().zip(solution.iter()).any(|(name, value)| name.starts_with("artificial_") && *value > 0.0)
+    }
+}
+
+struct StandardRow
+{
+    name: String,
+    coefficients: Vec<f64>,
+    relation: Relation,
+    rhs: f64
+}
+
+// the largest cost an artificial column gets in proportion to the costs of the model, unless a big_m is given
+const BIG_M_FACTOR: f64 = 1000.0;
+
+// every variable is shifted or split until it is >= 0, finite upper bounds become rows, every row gets b >= 0 and a
+// slack or artificial column for the identity basis the solver starts from. those come first, then the model's
+// columns, then the surplus columns of >= rows. artificial columns cost big_m, which is the big M method
+pub fn to_standard_form(model: &Model, big_m: Option<f64>) -> Result<StandardForm, String>
+{
+    let sign = match model.sense { Sense::Maximize => 1.0, Sense::Minimize => -1.0 };
+
+    let mut structural_costs: Vec<f64> = Vec::new();
+    let mut structural_names: Vec<String> = Vec::new();
+    // for every variable of the model, its columns among the structural ones and the factor it appears with
+    let mut substitutions: Vec<(f64, Vec<(usize, f64)>)> = Vec::new();
+    let mut offset = 0.0;
+    let mut bound_rows: Vec<(String, usize, f64)> = Vec::new();
+
+    for (index, variable) in model.variables.iter().enumerate()
+    {
+        let cost = model.objective.get(index).copied().unwrap_or(0.0);
+
+        if variable.lower > variable.upper
+        {
+            return Err(format!("The bounds of {} are empty: {} > {}", variable.name, variable.lower, variable.upper));
+        }
+
+        if variable.lower.is_finite()
+        {
+            // x = lower + x'
+            let column = structural_names.len();
+            structural_names.push(variable.name.clone());
+            structural_costs.push(cost);
+            offset += cost * variable.lower;
+            substitutions.push((variable.lower, vec![(column, 1.0)]));
+
+            if variable.upper.is_finite()
+            {
+                bound_rows.push((format!("bound_{}", variable.name), column, variable.upper - variable.lower));
+            }
+        }
+        else if variable.upper.is_finite()
+        {
+            // x = upper - x'
+            let column = structural_names.len();
+            structural_names.push(format!("{}_neg", variable.name));
+            structural_costs.push(-cost);
+            offset += cost * variable.upper;
+            substitutions.push((variable.upper, vec![(column, -1.0)]));
+        }
+        else
+        {
+            // x = x+ - x-
+            let column = structural_names.len();
+            structural_names.push(format!("{}_pos", variable.name));
+            structural_names.push(format!("{}_neg", variable.name));
+            structural_costs.push(cost);
+            structural_costs.push(-cost);
+            substitutions.push((0.0, vec![(column, 1.0), (column + 1, -1.0)]));
+        }
+    }
+
+    let structural = structural_names.len();
+    let mut rows: Vec<StandardRow> = Vec::new();
+
+    for constraint in model.constraints.iter()
+    {
+        let mut coefficients = vec![0.0; structural];
+        let mut rhs = constraint.rhs;
+
+        for (index, value) in constraint.coefficients.iter().enumerate().filter(|(_, x)| **x != 0.0)
+        {
+            let (shift, columns) = &substitutions[index];
+            rhs -= value * shift;
+            for (column, factor) in columns.iter()
+            {
+                coefficients[*column] += value * factor;
+            }
+        }
+
+        rows.push(StandardRow { name: constraint.name.clone(), coefficients: coefficients, relation: constraint.relation, rhs: rhs });
+    }
+
+    for (name, column, upper) in bound_rows.into_iter()
+    {
+        let mut coefficients = vec![0.0; structural];
+        coefficients[column] = 1.0;
+        rows.push(StandardRow { name: name, coefficients: coefficients, relation: Relation::LessEqual, rhs: upper });
+    }
+
+    if rows.is_empty()
+    {
+        return Err(format!("The model has no constraints, the solver needs at least one row"));
+    }
+
+    for row in rows.iter_mut().filter(|row| row.rhs < 0.0)
+    {
+        row.coefficients.iter_mut().for_each(|x| *x = -*x);
+        row.rhs = -row.rhs;
+        row.relation = row.relation.flipped();
+    }
+
+    let big_m = big_m.unwrap_or(BIG_M_FACTOR * structural_costs.iter().fold(1.0_f64, |largest, x| largest.max(x.abs())));
+    let m = rows.len();
+    let surplus_rows: Vec<usize> = (0..m).filter(|row| rows[*row].relation == Relation::GreaterEqual).collect();
+    let width = m + structural + surplus_rows.len();
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut costs: Vec<f64> = Vec::new();
+
+    for row in rows.iter()
+    {
+        match row.relation
+        {
+            Relation::LessEqual =>
+            {
+                columns.push(format!("slack_{}", row.name));
+                costs.push(0.0);
+            },
+            _ =>
+            {
+                columns.push(format!("artificial_{}", row.name));
+                costs.push(-big_m);
+            }
+        }
+    }
+
+    columns.extend(structural_names);
+    costs.extend(structural_costs.iter().map(|cost| sign * cost));
+
+    for row in surplus_rows.iter()
+    {
+        columns.push(format!("surplus_{}", rows[*row].name));
+        costs.push(0.0);
+    }
+
+    let tableau: Vec<Row> = rows.iter().enumerate().map(|(index, row)|
+    {
+        let mut a_ij = vec![0.0_f32; width];
+        a_ij[index] = 1.0;
+
+        for (column, value) in row.coefficients.iter().enumerate()
+        {
+            a_ij[m + column] = *value as f32;
+        }
+
+        if let Some(position) = surplus_rows.iter().position(|surplus| *surplus == index)
+        {
+            a_ij[m + structural + position] = -1.0;
+        }
+
+        Row { a_ij: a_ij, b_i: row.rhs as f32 }
+    }).collect();
+
+    // the solver prices the starting basis as if it cost nothing, so the cost of every artificial column is moved onto
+    // the columns of its row: -M a = -M (b - row x) on every feasible point, the -M b goes to the offset
+    let mut offset = sign * offset;
+    for (row, _) in tableau.iter().zip(rows.iter()).filter(|(_, row)| row.relation != Relation::LessEqual)
+    {
+        for (column, value) in row.a_ij.iter().enumerate()
+        {
+            costs[column] += big_m * *value as f64;
+        }
+        offset -= big_m * row.b_i as f64;
+    }
+
+    let mut solution: Vec<f32> = tableau.iter().map(|row| row.b_i).collect();
+    solution.resize(width, 0.0);
+
+    let variables = model.variables.iter().zip(model.objective.iter()).zip(substitutions).map(|((variable, cost), (shift, substituted))|
+    {
+        VariableMap { name: variable.name.clone(), cost: *cost, shift: shift, columns: substituted.into_iter().map(|(column, factor)| (m + column, factor)).collect() }
+    }).collect();
+
+    Ok(StandardForm
+    {
+        linear_program: LinearProgram { tableau: tableau, costs: costs.into_iter().map(|cost| cost as f32).collect(), relative_costs: Row { a_ij: vec![0.0; width], b_i: 0.0 }, solution: solution },
+        columns: columns,
+        variables: variables,
+        sense: model.sense,
+        offset: offset
+    })
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::model::{Model, Relation, Sense};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section
+{
+    None,
+    ObjectiveSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds
+}
+
+fn number(line: usize, text: &str) -> Result<f64, String>
+{
+    text.parse::<f64>().map_err(|_| format!("line {}: {} is not a number", line, text))
+}
+
+// free MPS, the fields are split on whitespace so names cannot have spaces in them. fixed MPS files without spaces in
+// their names read the same way. integer markers are skipped, the solver only knows continuous variables
+pub fn read(text: &str) -> Result<Model, String>
+{
+    let mut model = Model::new("model");
+    let mut section = Section::None;
+    let mut objective: Option<String> = None;
+    // the index of every constraint by name, the objective is not one of them
+    let mut rows: HashMap<String, usize> = HashMap::new();
+    let mut ranges: Vec<(usize, f64)> = Vec::new();
+
+    for (index, raw) in text.lines().enumerate()
+    {
+        let line = index + 1;
+        if raw.trim().is_empty() || raw.starts_with('*')
+        {
+            continue;
+        }
+
+        let fields: Vec<&str> = raw.split_whitespace().collect();
+
+        // section headers start in the first column, data lines are indented
+        if !raw.starts_with(' ') && !raw.starts_with('\t')
+        {
+            section = match fields[0].to_uppercase().as_str()
+            {
+                "NAME" =>
+                {
+                    model.name = fields.get(1).map_or(String::from("model"), |name| String::from(*name));
+                    Section::None
+                },
+                "OBJSENSE" =>
+                {
+                    match fields.get(1).map(|x| x.to_uppercase())
+                    {
+                        Some(sense) => model.sense = parse_sense(line, &sense)?,
+                        None => ()
+                    };
+                    Section::ObjectiveSense
+                },
+                "ROWS" => Section::Rows,
+                "COLUMNS" => Section::Columns,
+                "RHS" => Section::Rhs,
+                "RANGES" => Section::Ranges,
+                "BOUNDS" => Section::Bounds,
+                "ENDATA" => break,
+                other => return Err(format!("line {}: {} is not an MPS section", line, other))
+            };
+            continue;
+        }
+
+        match section
+        {
+            Section::None => return Err(format!("line {}: data before the first section", line)),
+            Section::ObjectiveSense => model.sense = parse_sense(line, &fields[0].to_uppercase())?,
+            Section::Rows =>
+            {
+                if fields.len() < 2
+                {
+                    return Err(format!("line {}: a row needs a type and a name", line));
+                }
+
+                let relation = match fields[0].to_uppercase().as_str()
+                {
+                    "N" =>
+                    {
+                        // only the first free row is the objective, the others are dropped like most solvers do
+                        if objective.is_none()
+                        {
+                            objective = Some(String::from(fields[1]));
+                            model.objective_name = String::from(fields[1]);
+                        }
+                        continue;
+                    },
+                    "L" => Relation::LessEqual,
+                    "G" => Relation::GreaterEqual,
+                    "E" => Relation::Equal,
+                    other => return Err(format!("line {}: {} is not a row type, use N, L, G or E", line, other))
+                };
+
+                rows.insert(String::from(fields[1]), model.constraints.len());
+                model.add_constraint(fields[1], &Vec::new(), relation, 0.0);
+            },
+            Section::Columns =>
+            {
+                if fields.get(1).map_or(false, |x| x.trim_matches('\'').eq_ignore_ascii_case("MARKER"))
+                {
+                    continue;
+                }
+
+                if fields.len() < 3 || fields.len() % 2 == 0
+                {
+                    return Err(format!("line {}: a column line is a column name followed by row and value pairs", line));
+                }
+
+                let column = model.variable(fields[0]);
+                for pair in fields[1..].chunks(2)
+                {
+                    let value = number(line, pair[1])?;
+
+                    if Some(pair[0]) == objective.as_deref()
+                    {
+                        model.objective[column] += value;
+                    }
+                    else
+                    {
+                        match rows.get(pair[0])
+                        {
+                            Some(row) => model.constraints[*row].coefficients[column] += value,
+                            None => return Err(format!("line {}: there is no row {}", line, pair[0]))
+                        };
+                    }
+                }
+            },
+            Section::Rhs | Section::Ranges =>
+            {
+                // the set name in front is optional
+                let pairs = if fields.len() % 2 == 1 { &fields[1..] } else { &fields[..] };
+
+                for pair in pairs.chunks(2)
+                {
+                    let value = number(line, pair[1])?;
+
+                    if Some(pair[0]) == objective.as_deref()
+                    {
+                        continue;
+                    }
+
+                    let row = match rows.get(pair[0])
+                    {
+                        Some(row) => *row,
+                        None => return Err(format!("line {}: there is no row {}", line, pair[0]))
+                    };
+
+                    if section == Section::Rhs
+                    {
+                        model.constraints[row].rhs = value;
+                    }
+                    else
+                    {
+                        ranges.push((row, value));
+                    }
+                }
+            },
+            Section::Bounds =>
+            {
+                if fields.len() < 3
+                {
+                    return Err(format!("line {}: a bound needs a type, a set name and a column", line));
+                }
+
+                let kind = fields[0].to_uppercase();
+                let column = match model.variables.iter().position(|variable| variable.name == fields[2])
+                {
+                    Some(column) => column,
+                    None => return Err(format!("line {}: there is no column {}", line, fields[2]))
+                };
+
+                let value = match fields.get(3)
+                {
+                    Some(value) => Some(number(line, value)?),
+                    None => None
+                };
+                let value_of = |value: Option<f64>| value.ok_or(format!("line {}: a {} bound needs a value", line, kind));
+                let variable = &mut model.variables[column];
+
+                match kind.as_str()
+                {
+                    "UP" | "UI" =>
+                    {
+                        let value = value_of(value)?;
+                        // an upper bound below 0 with no lower bound given makes the variable free below, as the
+                        // original MPS format has it
+                        if value < 0.0 && variable.lower == 0.0
+                        {
+                            variable.lower = f64::NEG_INFINITY;
+                        }
+                        variable.upper = value;
+                    },
+                    "LO" | "LI" => variable.lower = value_of(value)?,
+                    "FX" =>
+                    {
+                        variable.lower = value_of(value)?;
+                        variable.upper = variable.lower;
+                    },
+                    "FR" =>
+                    {
+                        variable.lower = f64::NEG_INFINITY;
+                        variable.upper = f64::INFINITY;
+                    },
+                    "MI" => variable.lower = f64::NEG_INFINITY,
+                    "PL" => variable.upper = f64::INFINITY,
+                    "BV" =>
+                    {
+                        variable.lower = 0.0;
+                        variable.upper = 1.0;
+                    },
+                    other => return Err(format!("line {}: {} is not a bound type", line, other))
+                };
+            }
+        };
+    }
+
+    // a range turns a row into lower <= row <= upper, the second half is a row of its own
+    for (row, range) in ranges.into_iter()
+    {
+        let constraint = model.constraints[row].clone();
+
+        // the relation the row keeps, and the relation and rhs of the other half
+        let (kept, relation, rhs) = match constraint.relation
+        {
+            Relation::LessEqual => (Relation::LessEqual, Relation::GreaterEqual, constraint.rhs - range.abs()),
+            Relation::GreaterEqual => (Relation::GreaterEqual, Relation::LessEqual, constraint.rhs + range.abs()),
+            Relation::Equal if range >= 0.0 => (Relation::GreaterEqual, Relation::LessEqual, constraint.rhs + range),
+            Relation::Equal => (Relation::LessEqual, Relation::GreaterEqual, constraint.rhs + range)
+        };
+
+        model.constraints[row].relation = kept;
+        let terms: Vec<(usize, f64)> = constraint.coefficients.iter().copied().enumerate().filter(|(_, x)| *x != 0.0).collect();
+        model.add_constraint(&format!("{}_range", constraint.name), &terms, relation, rhs);
+    }
+
+    Ok(model)
+}
+
+fn parse_sense(line: usize, sense: &str) -> Result<Sense, String>
+{
+    match sense
+    {
+        "MIN" | "MINIMIZE" => Ok(Sense::Minimize),
+        "MAX" | "MAXIMIZE" => Ok(Sense::Maximize),
+        other => Err(format!("line {}: {} is not an objective sense, use MIN or MAX", line, other))
+    }
+}
+
+// free MPS with an OBJSENSE section when the model is maximized
+pub fn write(model: &Model) -> String
+{
+    let mut text = String::new();
+    let _ = writeln!(text, "NAME          {}", model.name);
+
+    if model.sense == Sense::Maximize
+    {
+        let _ = writeln!(text, "OBJSENSE\n    MAX");
+    }
+
+    let _ = writeln!(text, "ROWS\n N  {}", model.objective_name);
+    for constraint in model.constraints.iter()
+    {
+        let kind = match constraint.relation
+        {
+            Relation::LessEqual => "L",
+            Relation::GreaterEqual => "G",
+            Relation::Equal => "E"
+        };
+        let _ = writeln!(text, " {}  {}", kind, constraint.name);
+    }
+
+    let _ = writeln!(text, "COLUMNS");
+    for (column, variable) in model.variables.iter().enumerate()
+    {
+        let cost = model.objective.get(column).copied().unwrap_or(0.0);
+        let mut entries: Vec<(&str, f64)> = Vec::new();
+
+        // a column with no entries at all would disappear, so its cost is written even when it is 0
+        if cost != 0.0 || model.constraints.iter().all(|constraint| constraint.coefficients[column] == 0.0)
+        {
+            entries.push((&model.objective_name, cost));
+        }
+        entries.extend(model.constraints.iter().filter(|constraint| constraint.coefficients[column] != 0.0).map(|constraint| (constraint.name.as_str(), constraint.coefficients[column])));
+
+        for (row, value) in entries.iter()
+        {
+            let _ = writeln!(text, "    {:<10} {:<10} {}", variable.name, row, value);
+        }
+    }
+
+    let _ = writeln!(text, "RHS");
+    for constraint in model.constraints.iter().filter(|constraint| constraint.rhs != 0.0)
+    {
+        let _ = writeln!(text, "    {:<10} {:<10} {}", "RHS", constraint.name, constraint.rhs);
+    }
+
+    let bounds: Vec<String> = model.variables.iter().flat_map(|variable|
+    {
+        let mut lines: Vec<String> = Vec::new();
+        match (variable.lower, variable.upper)
+        {
+            (lower, upper) if lower == upper => lines.push(format!(" FX BND       {:<10} {}", variable.name, lower)),
+            (lower, upper) if lower.is_infinite() && upper.is_infinite() => lines.push(format!(" FR BND       {}", variable.name)),
+            (lower, upper) =>
+            {
+                if lower.is_infinite() { lines.push(format!(" MI BND       {}", variable.name)); }
+                else if lower != 0.0 { lines.push(format!(" LO BND       {:<10} {}", variable.name, lower)); }
+
+                if upper.is_finite() { lines.push(format!(" UP BND       {:<10} {}", variable.name, upper)); }
+            }
+        };
+        lines
+    }).collect();
+
+    if !bounds.is_empty()
+    {
+        let _ = writeln!(text, "BOUNDS\n{}", bounds.join("\n"));
+    }
+
+    let _ = writeln!(text, "ENDATA");
+    text
+}
@@ -0,0 +1,400 @@
+use rocket::fairing::AdHoc;
+use rocket::response::content::RawHtml;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use schemars::JsonSchema;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use serde_json::{json, Map, Value};
+
+use crate::auth::{ApiKey, KeyUsage};
+use crate::batch::{BatchItem, BatchRequest};
+use crate::cache::CacheStatistics;
+use crate::error::SimplexError;
+use crate::health::{Liveness, Readiness, VersionInfo};
+use crate::jobs::{JobInfo, JobResult};
+use crate::row_arithmetic::{LinearProgram, PivotRule};
+use crate::store::{RunSummary, StoredRun};
+use crate::v1::SolveResult;
+use crate::validate::ValidationReport;
+use crate::{LimitsQuery, LinearProgramResponse, SolveQuery};
+
+// swagger ui is loaded from a cdn so the backend does not have to ship its assets
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Simplex API</title>
+<link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+<script>
+window.onload = () => { window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" }); };
+</script>
+</body>
+</html>
+"##;
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema
+{
+    generator.subschema_for::<T>()
+}
+
+enum Content
+{
+    Json(SchemaFn),
+    // a media type whose body is not json, e.g. text/html
+    Text(&'static str),
+    Empty
+}
+
+struct Operation
+{
+    summary: &'static str,
+    body: Option<SchemaFn>,
+    responses: Vec<(u16, &'static str, Content)>
+}
+
+// the parts of an operation that cannot be read off the mounted route, keyed by the name of the handler
+fn operation(name: &str) -> Option<Operation>
+{
+    let solve_errors = || vec![
+        (400, "The body is not json", Content::Json(schema::<LinearProgramResponse>)),
+        (413, "The body or the problem is over a configured limit, details.limit names it", Content::Json(schema::<LinearProgramResponse>)),
+        (422, "The body is not a linear program", Content::Json(schema::<LinearProgramResponse>))];
+
+    let with = |mut responses: Vec<(u16, &'static str, Content)>, more: Vec<(u16, &'static str, Content)>| { responses.extend(more); responses };
+
+    let operation = match name
+    {
+        "hello_world" => Operation { summary: "Says hello", body: None, responses: vec![(200, "Hello world", Content::Json(schema::<String>))] },
+        "preflight" => Operation { summary: "CORS preflight, answered for every path", body: None, responses: vec![(204, "The CORS headers allowed for the origin", Content::Empty)] },
+        "index" => Operation
+        {
+            summary: "Solves a linear program, every outcome including errors is a 200",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![(200, "The solution, unboundedness, a reached limit or an error", Content::Json(schema::<LinearProgramResponse>))], solve_errors())
+        },
+        "solve" => Operation
+        {
+            summary: "Solves a linear program",
+            body: Some(schema::<LinearProgram>),
+            responses: vec![
+                (200, "Optimal, unbounded or stopped by a limit, see status", Content::Json(schema::<SolveResult>)),
+                (400, "The body is not json", Content::Json(schema::<SimplexError>)),
+                (413, "The body or the problem is over a configured limit, or the requested vertex enumeration is too large", Content::Json(schema::<SimplexError>)),
+                (422, "The linear program failed its checks", Content::Json(schema::<SimplexError>)),
+                (500, "The solver failed on a valid problem", Content::Json(schema::<SimplexError>)),
+                (503, "The server is shutting down", Content::Json(schema::<SimplexError>))]
+        },
+        "validate_program" => Operation
+        {
+            summary: "Runs every check on a linear program without solving it",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![(200, "Every finding, valid is false if any of them is an error", Content::Json(schema::<ValidationReport>))], solve_errors())
+        },
+        "render_tableau" => Operation
+        {
+            summary: "Renders the tableau, or every tableau of the solve with trace, as html or latex depending on Accept",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![
+                (200, "The rendered tableau", Content::Text("text/html")),
+                (406, "Neither html nor latex is acceptable", Content::Json(schema::<LinearProgramResponse>))], solve_errors())
+        },
+        "visualize_feasible_region" => Operation
+        {
+            summary: "Draws the feasible region and the simplex path of a problem with 2 or 3 variables",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![(200, "An svg image, or an error as json", Content::Text("image/svg+xml"))], solve_errors())
+        },
+        "stream_solve" => Operation
+        {
+            summary: "Solves a linear program and streams every iteration as a server-sent event",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![(200, "iteration events followed by one result event", Content::Text("text/event-stream"))], solve_errors())
+        },
+        "batch_solve" => Operation
+        {
+            summary: "Solves a list of problems, or scenarios of one base problem, in parallel",
+            body: Some(schema::<BatchRequest>),
+            responses: with(vec![
+                (200, "One item per problem in the order they were sent", Content::Json(schema::<Vec<BatchItem>>)),
+                (413, "The batch has too many problems or a body over a configured limit", Content::Json(schema::<LinearProgramResponse>))], solve_errors())
+        },
+        "submit_job" => Operation
+        {
+            summary: "Queues a linear program to be solved in the background",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![
+                (202, "The queued job", Content::Json(schema::<JobInfo>)),
+                (503, "The job queue is full or the server is shutting down", Content::Json(schema::<LinearProgramResponse>))], solve_errors())
+        },
+        "job_status" => Operation
+        {
+            summary: "Status and progress of a job",
+            body: None,
+            responses: vec![(200, "The job", Content::Json(schema::<JobInfo>)), (404, "No such job for this key", Content::Empty)]
+        },
+        "job_result" => Operation
+        {
+            summary: "The job together with its result, which is null until the job stops",
+            body: None,
+            responses: vec![(200, "The job and its result", Content::Json(schema::<JobResult>)), (404, "No such job for this key", Content::Empty)]
+        },
+        "cancel_job" => Operation
+        {
+            summary: "Cancels a queued or running job",
+            body: None,
+            responses: vec![(200, "The job", Content::Json(schema::<JobInfo>)), (404, "No such job for this key", Content::Empty)]
+        },
+        "create_run" => Operation
+        {
+            summary: "Solves a linear program and stores it with its result",
+            body: Some(schema::<LinearProgram>),
+            responses: with(vec![
+                (200, "The stored run", Content::Json(schema::<StoredRun>)),
+                (500, "The store could not be written", Content::Json(schema::<LinearProgramResponse>))], solve_errors())
+        },
+        "list_runs" => Operation
+        {
+            summary: "Stored runs of the key, newest first, a page of at most limit runs after skipping offset of them",
+            body: None,
+            responses: vec![(200, "The runs", Content::Json(schema::<Vec<RunSummary>>)), (500, "The store could not be read", Content::Json(schema::<LinearProgramResponse>))]
+        },
+        "get_run" => Operation
+        {
+            summary: "A stored run",
+            body: None,
+            responses: vec![(200, "The run", Content::Json(schema::<StoredRun>)), (404, "No such run for this key", Content::Empty)]
+        },
+        "resolve_run" => Operation
+        {
+            summary: "Solves a stored run again and replaces its result",
+            body: None,
+            responses: vec![(200, "The run with its new result", Content::Json(schema::<StoredRun>)), (404, "No such run for this key", Content::Empty)]
+        },
+        "delete_run" => Operation
+        {
+            summary: "Deletes a stored run",
+            body: None,
+            responses: vec![(200, "The id of the deleted run", Content::Json(schema::<u64>)), (404, "No such run for this key", Content::Empty)]
+        },
+        "cache_statistics" => Operation
+        {
+            summary: "Size and hit rate of the result cache",
+            body: None,
+            responses: vec![(200, "The statistics", Content::Json(schema::<CacheStatistics>))]
+        },
+        "key_usage" => Operation
+        {
+            summary: "Requests, solves, iterations and wall time counted against the key of the request",
+            body: None,
+            responses: vec![(200, "The usage of the key", Content::Json(schema::<KeyUsage>)), (404, "API keys are turned off", Content::Json(schema::<SimplexError>))]
+        },
+        "list_keys" => Operation
+        {
+            summary: "The usage of every key, needs an admin key",
+            body: None,
+            responses: vec![(200, "The usage of every key", Content::Json(schema::<Vec<KeyUsage>>)), (403, "The key is not an admin key", Content::Json(schema::<SimplexError>))]
+        },
+        "add_key" => Operation
+        {
+            summary: "Adds a key to the store or replaces the key with the same name, needs an admin key",
+            body: Some(schema::<ApiKey>),
+            responses: vec![
+                (201, "The added key", Content::Json(schema::<ApiKey>)),
+                (403, "The key is not an admin key", Content::Json(schema::<SimplexError>)),
+                (422, "The key has no name or is in use under another name", Content::Json(schema::<SimplexError>))]
+        },
+        "remove_key" => Operation
+        {
+            summary: "Removes a key from the store, keys from the keys file cannot be removed, needs an admin key",
+            body: None,
+            responses: vec![
+                (200, "The name of the removed key", Content::Json(schema::<String>)),
+                (403, "The key is not an admin key", Content::Json(schema::<SimplexError>)),
+                (404, "No such key in the store", Content::Empty)]
+        },
+        "metrics" => Operation { summary: "Request, solve and problem size metrics for Prometheus", body: None, responses: vec![(200, "The metrics in the Prometheus text format", Content::Text("text/plain"))] },
+        "health" => Operation { summary: "Liveness, answers whenever the server takes requests", body: None, responses: vec![(200, "The server is up", Content::Json(schema::<Liveness>))] },
+        "ready" => Operation
+        {
+            summary: "Readiness, solves a small known problem and reads from the store",
+            body: None,
+            responses: vec![(200, "Every check passed", Content::Json(schema::<Readiness>)), (503, "At least one check failed", Content::Json(schema::<Readiness>))]
+        },
+        "version" => Operation { summary: "The build version and the solver features turned on", body: None, responses: vec![(200, "The version", Content::Json(schema::<VersionInfo>))] },
+        "openapi_document" => Operation { summary: "This document", body: None, responses: vec![(200, "An OpenAPI 3 document", Content::Text("application/json"))] },
+        "documentation" => Operation { summary: "Swagger UI for this document", body: None, responses: vec![(200, "An html page", Content::Text("text/html"))] },
+        _ => return None
+    };
+
+    Some(operation)
+}
+
+// the same handler can be mounted under / and /v1, the operation id tells them apart
+fn operation_id(route: &Route, name: &str) -> String
+{
+    let base = route.uri.base().trim_matches('/').replace('/', "_");
+    if base.is_empty() { String::from(name) } else { format!("{}_{}", base, name) }
+}
+
+// the dynamic segments of a route, <id> in the path or <tags> in the query, without the trailing .. of a catch-all
+fn dynamic_segments(uri: &str, separator: char) -> Vec<String>
+{
+    uri.split(separator)
+        .filter_map(|segment| segment.strip_prefix('<').and_then(|x| x.strip_suffix('>')))
+        .map(|x| String::from(x.trim_end_matches("..")))
+        .collect()
+}
+
+fn parameter_schema(name: &str, generator: &mut SchemaGenerator) -> Value
+{
+    match name
+    {
+        "alternatives" | "vertices" | "trace" | "tableau" => json!({ "type": "boolean" }),
+        "pivot_rule" => to_value(&generator.subschema_for::<PivotRule>()),
+        "id" | "max_iterations" | "time_limit_ms" | "offset" | "limit" => json!({ "type": "integer", "minimum": 0 }),
+        "tags" => json!({ "type": "array", "items": { "type": "string" } }),
+        _ => json!({ "type": "string" })
+    }
+}
+
+fn to_value(schema: &Schema) -> Value
+{
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+fn content(content: &Content, generator: &mut SchemaGenerator) -> Option<Value>
+{
+    match content
+    {
+        Content::Json(schema) => Some(json!({ "application/json": { "schema": to_value(&schema(generator)) } })),
+        Content::Text(media_type) => Some(json!({ *media_type: {} })),
+        Content::Empty => None
+    }
+}
+
+// walks the routes that are actually mounted so a route cannot be missing from the document, only undescribed
+pub fn document<'a>(routes: impl Iterator<Item = &'a Route>) -> Value
+{
+    let mut generator = SchemaSettings::openapi3().into_generator();
+    let mut paths = Map::new();
+
+    for route in routes
+    {
+        let name = route.name.as_deref().unwrap_or("");
+
+        let path = route.uri.path().split('/').map(|segment| match segment.strip_prefix('<').and_then(|x| x.strip_suffix('>'))
+        {
+            Some(parameter) => format!("{{{}}}", parameter.trim_end_matches("..")),
+            None => String::from(segment)
+        }).collect::<Vec<String>>().join("/");
+
+        let mut parameters: Vec<Value> = Vec::new();
+        for parameter in dynamic_segments(route.uri.path(), '/')
+        {
+            parameters.push(json!({ "name": parameter, "in": "path", "required": true, "schema": parameter_schema(&parameter, &mut generator) }));
+        }
+        // a query struct taken whole, e.g. <limits..>, stands for each of its fields
+        let query = dynamic_segments(route.uri.query().unwrap_or(""), '&').into_iter().flat_map(|parameter| match parameter.as_str()
+        {
+            "solve" => SolveQuery::FIELDS.iter().map(|x| String::from(*x)).collect(),
+            "limits" => LimitsQuery::FIELDS.iter().map(|x| String::from(*x)).collect(),
+            _ => vec![parameter]
+        }).collect::<Vec<String>>();
+
+        for parameter in query
+        {
+            parameters.push(json!({ "name": parameter, "in": "query", "required": false, "schema": parameter_schema(&parameter, &mut generator) }));
+        }
+
+        let mut operation_json = json!({ "operationId": operation_id(route, name), "parameters": parameters });
+
+        match operation(name)
+        {
+            Some(operation) =>
+            {
+                operation_json["summary"] = json!(operation.summary);
+
+                if let Some(body) = operation.body
+                {
+                    operation_json["requestBody"] = json!({ "required": true, "content": { "application/json": { "schema": to_value(&body(&mut generator)) } } });
+                }
+
+                let mut responses = Map::new();
+                for (status, description, response_content) in operation.responses.iter()
+                {
+                    let mut response = json!({ "description": description });
+                    if let Some(response_content) = content(response_content, &mut generator)
+                    {
+                        response["content"] = response_content;
+                    }
+                    // the responses particular to the operation come first and win over the shared ones
+                    responses.entry(status.to_string()).or_insert(response);
+                }
+                operation_json["responses"] = Value::Object(responses);
+            },
+            None => operation_json["responses"] = json!({ "default": { "description": "Not described yet" } })
+        };
+
+        let entry = paths.entry(path).or_insert_with(|| json!({}));
+        entry[route.method.as_str().to_lowercase()] = operation_json;
+    }
+
+    let schemas: Map<String, Value> = generator.take_definitions().iter().map(|(name, schema)| (name.clone(), to_value(schema))).collect();
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Simplex solver", "version": env!("CARGO_PKG_VERSION") },
+        "paths": paths,
+        // the key is only needed when the server has API keys turned on
+        "security": [{ "ApiKey": [] }, {}],
+        "components":
+        {
+            "schemas": schemas,
+            "securitySchemes": { "ApiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" } }
+        }
+    })
+}
+
+// built once the routes are mounted, they do not change after that
+pub struct OpenApiDocument(pub Value);
+
+pub fn fairing() -> AdHoc
+{
+    AdHoc::on_ignite("OpenAPI document", |rocket| async
+    {
+        let document = document(rocket.routes());
+        rocket.manage(OpenApiDocument(document))
+    })
+}
+
+#[get("/openapi.json")]
+fn openapi_document(document: &State<OpenApiDocument>) -> Json<Value>
+{
+    Json(document.0.clone())
+}
+
+#[get("/docs")]
+fn documentation() -> RawHtml<&'static str>
+{
+    RawHtml(SWAGGER_UI)
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![openapi_document, documentation]
+}
+
+// the handlers that no entry in operation describes, for the test that keeps the document complete
+#[cfg(test)]
+pub fn undescribed<'a>(routes: impl Iterator<Item = &'a Route>) -> Vec<String>
+{
+    routes.filter(|route| operation(route.name.as_deref().unwrap_or("")).is_none())
+        .map(|route| format!("{} {}", route.method, route.uri)).collect()
+}
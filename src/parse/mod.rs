@@ -0,0 +1,238 @@
+use std::ops::Deref;
+
+use rocket::data::{self, Data, FromData, Limits};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::Request;
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+
+use crate::config::{BatchConfig, ProblemLimitsConfig};
+use crate::error::{self, ErrorCode, JsonDiagnostic, SimplexError};
+use crate::limits;
+
+// the fields every linear program needs, a missing one gets pointed at the example file
+const LINEAR_PROGRAM_FIELDS: [&str; 4] = ["tableau", "costs", "relative_costs", "solution"];
+
+// a json body like rocket's Json, but a body that cannot be read leaves a diagnostic behind for the catchers
+pub struct JsonBody<T>(pub T);
+
+impl<T> JsonBody<T>
+{
+    pub fn into_inner(self) -> T
+    {
+        self.0
+    }
+}
+
+impl<T> Deref for JsonBody<T>
+{
+    type Target = T;
+
+    fn deref(&self) -> &T
+    {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned + Send> FromData<'r> for JsonBody<T>
+{
+    type Error = SimplexError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self>
+    {
+        let limit = request.limits().get("json").unwrap_or(Limits::JSON);
+
+        let text = match data.open(limit).into_string().await
+        {
+            Ok(text) if text.is_complete() => text.into_inner(),
+            Ok(_) => return fail(request, Status::PayloadTooLarge,
+                SimplexError::new(ErrorCode::TooLarge, format!("The request body is larger than {}, the limit set by limits.json", limit)).with_limit("json")),
+            Err(error) => return fail(request, Status::BadRequest, SimplexError::new(ErrorCode::InvalidJson, format!("The request body could not be read: {}", error)))
+        };
+
+        let problem_limits = request.rocket().state::<ProblemLimitsConfig>().cloned().unwrap_or_default();
+        let max_problems = request.rocket().state::<BatchConfig>().map(|batch_config| batch_config.max_problems);
+
+        match limits::parse_with_limits(&problem_limits, max_problems, || parse_json(&text))
+        {
+            (Ok(value), _) => Outcome::Success(JsonBody(value)),
+            (Err(_), Some(exceeded)) => fail(request, Status::PayloadTooLarge, exceeded),
+            (Err(error), None) =>
+            {
+                // well formed json of the wrong shape is a 422 the same way rocket's Json does it
+                let data_error = error.details.json.as_ref().map_or(false, |json| json.category == "data");
+                fail(request, if data_error { Status::UnprocessableEntity } else { Status::BadRequest }, error)
+            }
+        }
+    }
+}
+
+fn fail<'r, T>(request: &'r Request<'_>, status: Status, error: SimplexError) -> data::Outcome<'r, T, SimplexError>
+{
+    error::remember_failure(request, &error);
+    Outcome::Error((status, error))
+}
+
+pub fn parse_json<T: DeserializeOwned>(text: &str) -> Result<T, SimplexError>
+{
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+
+    let value: T = match serde_path_to_error::deserialize(&mut deserializer)
+    {
+        Ok(value) => value,
+        Err(error) =>
+        {
+            // the root of the document is written as a single dot
+            let path = error.path().to_string();
+            let path = if path == "." { String::new() } else { path };
+            return Err(diagnose(text, error.into_inner(), path));
+        }
+    };
+
+    match deserializer.end()
+    {
+        Ok(_) => Ok(value),
+        Err(error) => Err(diagnose(text, error, String::new()))
+    }
+}
+
+fn diagnose(text: &str, error: serde_json::Error, path: String) -> SimplexError
+{
+    let category = match error.classify()
+    {
+        Category::Syntax => "syntax",
+        Category::Data => "data",
+        Category::Eof => "eof",
+        Category::Io => "io"
+    };
+
+    // serde_json puts the position at the end of the message, it is in the diagnostic already
+    let description = error.to_string();
+    let description = match description.rfind(" at line ")
+    {
+        Some(index) => String::from(&description[..index]),
+        None => description
+    };
+
+    let (expected, found) = expected_and_found(&description);
+    let suggestions = suggestions(text, &description, error.line(), error.column());
+
+    let message = if path.is_empty()
+    {
+        format!("The request body could not be read: {}", description)
+    }
+    else
+    {
+        format!("The field {} could not be read: {}", path, description)
+    };
+
+    let mut simplex_error = SimplexError::new(ErrorCode::InvalidJson, message);
+    simplex_error.details.json = Some(JsonDiagnostic
+    {
+        category: String::from(category), path: path, line: error.line(), column: error.column(),
+        expected: expected, found: found, suggestions: suggestions
+    });
+
+    simplex_error
+}
+
+// serde words its errors as "invalid type: <found>, expected <expected>" and similar
+fn expected_and_found(description: &str) -> (Option<String>, Option<String>)
+{
+    for prefix in ["invalid type: ", "invalid value: ", "invalid length "]
+    {
+        if let Some(rest) = description.strip_prefix(prefix)
+        {
+            return match rest.split_once(", expected ")
+            {
+                Some((found, expected)) => (Some(String::from(expected)), Some(String::from(found))),
+                None => (None, Some(String::from(rest)))
+            };
+        }
+    }
+
+    if let Some(field) = description.strip_prefix("missing field ")
+    {
+        return (Some(format!("field {}", field)), None);
+    }
+
+    match description.strip_prefix("expected ")
+    {
+        Some(expected) => (Some(String::from(expected)), None),
+        None => (None, None)
+    }
+}
+
+fn suggestions(text: &str, description: &str, line: usize, column: usize) -> Vec<String>
+{
+    let mut suggestions: Vec<String> = Vec::new();
+
+    if description == "missing field `relative_costs`"
+    {
+        suggestions.push(String::from("relative_costs is worked out by the solver, send {\"a_ij\": [0.0, ...], \"b_i\": 0.0} with one 0 per column"));
+    }
+    else if description == "missing field `solution`"
+    {
+        suggestions.push(String::from("solution is the starting basic solution, the b_i of every row followed by a 0 for every other column"));
+    }
+    else if LINEAR_PROGRAM_FIELDS.iter().any(|field| description == format!("missing field `{}`", field))
+    {
+        suggestions.push(String::from("A linear program needs tableau, costs, relative_costs and solution, see example_linearProgram.json"));
+    }
+
+    if description.starts_with("invalid type: string") && description.contains("expected f32")
+    {
+        suggestions.push(String::from("Numbers are written without quotes, 1.5 and not \"1.5\""));
+    }
+
+    if description.starts_with("trailing comma")
+    {
+        suggestions.push(String::from("Remove the comma after the last entry of the list or object"));
+    }
+
+    if description.starts_with("key must be a string")
+    {
+        suggestions.push(String::from("Keys need double quotes, e.g. \"b_i\": 1.0"));
+    }
+
+    match character_at(text, line, column)
+    {
+        Some('N') | Some('I') => suggestions.push(String::from("JSON has no NaN or Infinity, every entry has to be a finite number")),
+        Some('+') | Some('.') => suggestions.push(String::from("Numbers cannot start with + or a decimal point, write 0.5 instead of .5 or +0.5")),
+        Some('\'') => suggestions.push(String::from("Strings and keys need double quotes")),
+        _ => ()
+    };
+
+    if let Some(number) = decimal_comma(text)
+    {
+        suggestions.push(format!("{} looks like a number with a decimal comma, JSON reads it as two numbers, write {}", number, number.replace(',', ".")));
+    }
+
+    suggestions
+}
+
+// the character serde_json stopped at, its columns count from 1
+fn character_at(text: &str, line: usize, column: usize) -> Option<char>
+{
+    text.lines().nth(line.checked_sub(1)?)?.chars().nth(column.checked_sub(1)?)
+}
+
+// digits on both sides of a comma with no space, e.g. the 29,5 in example_row.json
+fn decimal_comma(text: &str) -> Option<String>
+{
+    let characters: Vec<char> = text.chars().collect();
+
+    for index in 1..characters.len().saturating_sub(1)
+    {
+        if characters[index] == ',' && characters[index - 1].is_ascii_digit() && characters[index + 1].is_ascii_digit()
+        {
+            let start = (0..index).rev().take_while(|x| characters[*x].is_ascii_digit() || characters[*x] == '.' || characters[*x] == '-').last().unwrap_or(index);
+            let end = (index + 1..characters.len()).take_while(|x| characters[*x].is_ascii_digit()).last().unwrap_or(index);
+            return Some(characters[start..=end].iter().collect());
+        }
+    }
+
+    None
+}
@@ -0,0 +1,206 @@
+use crate::row_arithmetic::{LinearProgram, Position, Row, TraceStep};
+
+#[derive(Clone, Copy)]
+pub enum RenderFormat
+{
+    Latex,
+    Html
+}
+
+impl TraceStep
+{
+    pub fn to_latex(&self) -> String
+    {
+        latex_array(&self.tableau, &self.relative_costs, &self.basis, self.pivot.as_ref())
+    }
+
+    pub fn to_html(&self) -> String
+    {
+        html_table(&self.tableau, &self.relative_costs, &self.basis, self.pivot.as_ref())
+    }
+
+    pub fn to_text(&self) -> String
+    {
+        text_table(&self.tableau, &self.relative_costs, &self.basis, self.pivot.as_ref())
+    }
+}
+
+pub fn render_program(linear_program: &LinearProgram, format: &RenderFormat) -> String
+{
+    let step = linear_program.trace_step(None);
+
+    match format
+    {
+        RenderFormat::Latex => format!("\\[\n{}\n\\]\n", step.to_latex()),
+        RenderFormat::Html => step.to_html()
+    }
+}
+
+pub fn render_trace(steps: &Vec<TraceStep>, format: &RenderFormat) -> String
+{
+    let mut rendered = String::new();
+
+    for (iteration, step) in steps.iter().enumerate()
+    {
+        match format
+        {
+            RenderFormat::Latex => rendered.push_str(&format!("% Iteration {}\n\\[\n{}\n\\]\n", iteration, step.to_latex())),
+            RenderFormat::Html => rendered.push_str(&format!("<h3>Iteration {}</h3>\n{}", iteration, step.to_html()))
+        }
+    }
+
+    rendered
+}
+
+fn is_basic(basis: &Vec<Option<usize>>, column: usize) -> bool
+{
+    basis.contains(&Some(column))
+}
+
+fn is_pivot(pivot: Option<&Position>, row: usize, column: usize) -> bool
+{
+    match pivot
+    {
+        Some(position) => position.row == row && position.column == column,
+        None => false
+    }
+}
+
+// the pivot element is boxed, basic columns have bold headers and the relative costs row sits under a rule
+pub fn latex_array(tableau: &Vec<Row>, relative_costs: &Row, basis: &Vec<Option<usize>>, pivot: Option<&Position>) -> String
+{
+    let columns = relative_costs.a_ij.len();
+    let mut latex = format!("\\begin{{array}}{{c|{}|c}}\n", "c".repeat(columns));
+
+    let header: Vec<String> = (0..columns).map(|column|
+    {
+        if is_basic(basis, column) { format!("\\mathbf{{x_{{{}}}}}", column + 1) } else { format!("x_{{{}}}", column + 1) }
+    }).collect();
+    latex.push_str(&format!(" & {} & b \\\\\n\\hline\n", header.join(" & ")));
+
+    for (row_index, row) in tableau.iter().enumerate()
+    {
+        let label = match basis.get(row_index)
+        {
+            Some(Some(column)) => format!("x_{{{}}}", column + 1),
+            _ => String::new()
+        };
+
+        let entries: Vec<String> = row.a_ij.iter().enumerate().map(|(column, value)|
+        {
+            if is_pivot(pivot, row_index, column) { format!("\\boxed{{{}}}", value) } else { format!("{}", value) }
+        }).collect();
+
+        latex.push_str(&format!("{} & {} & {} \\\\\n", label, entries.join(" & "), row.b_i));
+    }
+
+    let costs: Vec<String> = relative_costs.a_ij.iter().map(|value| format!("{}", value)).collect();
+    latex.push_str(&format!("\\hline\n\\bar{{c}} & {} & {}\n\\end{{array}}", costs.join(" & "), relative_costs.b_i));
+
+    latex
+}
+
+pub fn html_table(tableau: &Vec<Row>, relative_costs: &Row, basis: &Vec<Option<usize>>, pivot: Option<&Position>) -> String
+{
+    let columns = relative_costs.a_ij.len();
+    let mut html = String::from("<table class=\"tableau\">\n<thead>\n<tr><th></th>");
+
+    for column in 0..columns
+    {
+        if is_basic(basis, column)
+        {
+            html.push_str(&format!("<th class=\"basic\">x<sub>{}</sub></th>", column + 1));
+        }
+        else
+        {
+            html.push_str(&format!("<th>x<sub>{}</sub></th>", column + 1));
+        }
+    }
+    html.push_str("<th>b</th></tr>\n</thead>\n<tbody>\n");
+
+    for (row_index, row) in tableau.iter().enumerate()
+    {
+        match basis.get(row_index)
+        {
+            Some(Some(column)) => html.push_str(&format!("<tr><th>x<sub>{}</sub></th>", column + 1)),
+            _ => html.push_str("<tr><th></th>")
+        };
+
+        for (column, value) in row.a_ij.iter().enumerate()
+        {
+            let mut classes: Vec<&str> = Vec::new();
+            if is_basic(basis, column) { classes.push("basic"); }
+            if is_pivot(pivot, row_index, column) { classes.push("pivot"); }
+
+            if classes.len() == 0
+            {
+                html.push_str(&format!("<td>{}</td>", value));
+            }
+            else
+            {
+                html.push_str(&format!("<td class=\"{}\">{}</td>", classes.join(" "), value));
+            }
+        }
+
+        html.push_str(&format!("<td>{}</td></tr>\n", row.b_i));
+    }
+
+    html.push_str("<tr class=\"relative-costs\"><th>c&#772;</th>");
+    for value in &relative_costs.a_ij
+    {
+        html.push_str(&format!("<td>{}</td>", value));
+    }
+    html.push_str(&format!("<td>{}</td></tr>\n</tbody>\n</table>\n", relative_costs.b_i));
+
+    html
+}
+
+// for terminals, the pivot element is in brackets, basic columns are starred and the relative costs are the z row
+pub fn text_table(tableau: &Vec<Row>, relative_costs: &Row, basis: &Vec<Option<usize>>, pivot: Option<&Position>) -> String
+{
+    let columns = relative_costs.a_ij.len();
+    let mut lines: Vec<Vec<String>> = Vec::new();
+
+    let mut header = vec![String::new()];
+    header.extend((0..columns).map(|column| if is_basic(basis, column) { format!("x{}*", column + 1) } else { format!("x{}", column + 1) }));
+    header.push(String::from("b"));
+    lines.push(header);
+
+    for (row_index, row) in tableau.iter().enumerate()
+    {
+        let mut line = match basis.get(row_index)
+        {
+            Some(Some(column)) => vec![format!("x{}", column + 1)],
+            _ => vec![String::new()]
+        };
+
+        line.extend(row.a_ij.iter().enumerate().map(|(column, value)|
+        {
+            if is_pivot(pivot, row_index, column) { format!("[{}]", value) } else { format!("{}", value) }
+        }));
+        line.push(format!("{}", row.b_i));
+        lines.push(line);
+    }
+
+    let mut costs = vec![String::from("z")];
+    costs.extend(relative_costs.a_ij.iter().map(|value| format!("{}", value)));
+    costs.push(format!("{}", relative_costs.b_i));
+    lines.push(costs);
+
+    let widths: Vec<usize> = (0..columns + 2).map(|column| lines.iter().filter_map(|line| line.get(column)).map(|cell| cell.len()).max().unwrap_or(0)).collect();
+    let rule = format!("{}\n", "-".repeat(widths.iter().sum::<usize>() + 2 * widths.len()));
+
+    let mut text = String::new();
+    for (index, line) in lines.iter().enumerate()
+    {
+        if index == 1 || index == lines.len() - 1
+        {
+            text.push_str(&rule);
+        }
+
+        let cells: Vec<String> = line.iter().zip(widths.iter()).map(|(cell, width)| format!("{:>width$}", cell, width = width)).collect();
+        text.push_str(&format!("{}\n", cells.join("  ").trim_end()));
+    }
+
+    text
+}
@@ -0,0 +1,324 @@
+use std::fmt::Write;
+
+use crate::cli;
+use crate::render;
+use crate::row_arithmetic::{self, LinearProgram, PivotRule, Position, Row, SimplexResult};
+
+pub const HELP: &str = "\
+load FILE              read a problem in json, mps or lp, by its extension
+new C1 .. Cn           start an empty tableau with these costs
+add-row A1 .. An B     add a row to the tableau
+remove-row ROW         remove a row, rows count from 1 at the top
+start                  check the tableau and work out the relative costs of its starting basis
+show                   print the tableau
+costs                  print the relative costs and the columns that can enter
+solution               print the solution of the current basis
+pivot ROW COLUMN       pivot on an element, the column as x3 or 3
+step                   pivot once by the pivot rule
+rule NAME              lexicographic, bland or dantzig
+save FILE              write the tableau as json
+help                   print this
+quit                   leave";
+
+// what the repl works on between commands, every command is one call to execute so the session can be driven without
+// a terminal
+pub struct Session
+{
+    pub linear_program: Option<LinearProgram>,
+    pub pivot_rule: PivotRule,
+    pub iterations: usize,
+    // the relative costs belong to the tableau, either start passed or the tableau was loaded with them
+    pub started: bool
+}
+
+impl Default for Session
+{
+    fn default() -> Self
+    {
+        Session::new()
+    }
+}
+
+impl Session
+{
+    pub fn new() -> Self
+    {
+        Session { linear_program: None, pivot_rule: PivotRule::Lexicographic, iterations: 0, started: false }
+    }
+
+    // the text to print, None once the session is over
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>, String>
+    {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (command, arguments) = match words.split_first()
+        {
+            Some((command, arguments)) => (command.to_lowercase(), arguments),
+            None => return Ok(Some(String::new()))
+        };
+
+        let text = match command.as_str()
+        {
+            "quit" | "exit" => return Ok(None),
+            "help" => String::from(HELP),
+            "load" => self.load(arguments)?,
+            "new" => self.create(arguments)?,
+            "add-row" => self.add_row(arguments)?,
+            "remove-row" => self.remove_row(arguments)?,
+            "start" => self.start()?,
+            "show" => self.show(None)?,
+            "costs" => self.costs()?,
+            "solution" => self.solution()?,
+            "pivot" => self.pivot(arguments)?,
+            "step" => self.step()?,
+            "rule" =>
+            {
+                let name = single(arguments, "rule NAME")?;
+                self.pivot_rule = cli::parse_pivot_rule(name)?;
+                format!("pivot rule {:?}", self.pivot_rule)
+            },
+            "save" => self.save(arguments)?,
+            other => return Err(format!("{} is not a command, help lists them", other))
+        };
+
+        Ok(Some(text))
+    }
+
+    fn current(&self) -> Result<&LinearProgram, String>
+    {
+        self.linear_program.as_ref().ok_or(String::from("There is no tableau yet, load a problem or start a new one"))
+    }
+
+    fn current_mut(&mut self) -> Result<&mut LinearProgram, String>
+    {
+        self.linear_program.as_mut().ok_or(String::from("There is no tableau yet, load a problem or start a new one"))
+    }
+
+    // pivoting a tableau that start did not check could index past its rows or pivot on relative costs of all zeros
+    fn started(&mut self) -> Result<&mut LinearProgram, String>
+    {
+        let started = self.started;
+        let linear_program = self.current_mut()?;
+
+        match started
+        {
+            true => Ok(linear_program),
+            false => Err(String::from("The tableau has not passed start yet, which pivot, step, costs and solution need"))
+        }
+    }
+
+    fn load(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        let path = single(arguments, "load FILE")?;
+        let mut linear_program = cli::read_problem(Some(path), None)?.standard_form(None)?.linear_program;
+        linear_program.check_row_length().map_err(|error| format!("{}: {}", path, error.message))?;
+
+        // a starting tableau that passes the checks gets its relative costs worked out. a saved tableau that was already
+        // pivoted fails them since its identity moved, but it keeps the relative costs it was saved with. anything else
+        // has to pass start before it is pivoted on
+        let checked = row_arithmetic::perform_checks(&linear_program);
+        let started = match &checked
+        {
+            Ok(_) =>
+            {
+                linear_program.relative_costs = linear_program.calculate_costs();
+                true
+            },
+            Err(_) => linear_program.relative_costs.a_ij.iter().any(|x| *x != 0.0)
+        };
+
+        self.linear_program = Some(linear_program);
+        self.iterations = 0;
+        self.started = started;
+
+        match checked
+        {
+            Err(error) if !started => Ok(format!("{}\n{}, fix the tableau and run start", self.show(None)?, error.message)),
+            _ => self.show(None)
+        }
+    }
+
+    fn create(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        let costs = numbers(arguments)?;
+        if costs.is_empty()
+        {
+            return Err(String::from("A new tableau needs the cost of every column: new C1 .. Cn"));
+        }
+
+        let columns = costs.len();
+        self.linear_program = Some(LinearProgram { tableau: Vec::new(), costs: costs, relative_costs: Row { a_ij: vec![0.0; columns], b_i: 0.0 }, solution: vec![0.0; columns] });
+        self.iterations = 0;
+        self.started = false;
+        Ok(format!("new tableau with {} columns, add its rows with add-row", columns))
+    }
+
+    fn add_row(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        let mut values = numbers(arguments)?;
+        let linear_program = self.current_mut()?;
+        let columns = linear_program.costs.len();
+
+        if values.len() != columns + 1
+        {
+            return Err(format!("A row has {} entries and b, {} numbers were given", columns, values.len()));
+        }
+
+        let b_i = values.pop().unwrap_or(0.0);
+        linear_program.tableau.push(Row { a_ij: values, b_i: b_i });
+        self.started = false;
+        self.show(None)
+    }
+
+    fn remove_row(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        let linear_program = self.current_mut()?;
+        let row = row_number(single(arguments, "remove-row ROW")?, linear_program.tableau.len())?;
+        linear_program.tableau.remove(row);
+        self.started = false;
+        self.show(None)
+    }
+
+    // the same checks and starting costs the solver begins a solve with
+    fn start(&mut self) -> Result<String, String>
+    {
+        self.started = false;
+        let linear_program = self.current_mut()?;
+
+        // the starting solution is b in the identity columns
+        let rows = linear_program.tableau.len();
+        for (column, value) in linear_program.solution.iter_mut().enumerate()
+        {
+            *value = if column < rows { linear_program.tableau[column].b_i } else { 0.0 };
+        }
+
+        row_arithmetic::perform_checks(linear_program).map_err(|error| error.message)?;
+        linear_program.relative_costs = linear_program.calculate_costs();
+        self.iterations = 0;
+        self.started = true;
+        self.show(None)
+    }
+
+    fn show(&self, pivot: Option<&Position>) -> Result<String, String>
+    {
+        let linear_program = self.current()?;
+        Ok(render::text_table(&linear_program.tableau, &linear_program.relative_costs, &linear_program.basis(), pivot))
+    }
+
+    fn costs(&mut self) -> Result<String, String>
+    {
+        let linear_program = self.started()?;
+        let mut text = String::new();
+
+        for (column, value) in linear_program.relative_costs.a_ij.iter().enumerate()
+        {
+            let _ = writeln!(text, "  x{:<4} {}", column + 1, value);
+        }
+
+        let entering: Vec<String> = linear_program.relative_costs.a_ij.iter().enumerate().filter(|(_, x)| **x < 0.0).map(|(column, _)| format!("x{}", column + 1)).collect();
+        let _ = writeln!(text, "objective {}", linear_program.relative_costs.b_i);
+        let _ = write!(text, "{}", if entering.is_empty() { String::from("no column can enter, the basis is optimal") } else { format!("can enter: {}", entering.join(", ")) });
+        Ok(text)
+    }
+
+    fn solution(&mut self) -> Result<String, String>
+    {
+        let linear_program = self.started()?;
+        let mut text = String::new();
+
+        for (column, value) in linear_program.basic_solution().iter().enumerate()
+        {
+            let _ = writeln!(text, "  x{:<4} {}", column + 1, value);
+        }
+        let _ = write!(text, "objective {}", linear_program.relative_costs.b_i);
+        Ok(text)
+    }
+
+    fn pivot(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        if arguments.len() != 2
+        {
+            return Err(String::from("Expected pivot ROW COLUMN"));
+        }
+
+        let linear_program = self.started()?;
+        let row = row_number(arguments[0], linear_program.tableau.len())?;
+        let column = column_number(arguments[1], linear_program.costs.len())?;
+
+        // a 0 would make the row all infinities
+        let element = linear_program.tableau[row].a_ij.get(column).copied();
+        if element.unwrap_or(0.0) == 0.0
+        {
+            return Err(format!("The element in row {} and column x{} is 0, a pivot needs one that is not", row + 1, column + 1));
+        }
+
+        let position = Position::new(row, column);
+        linear_program.pivot(&position).map_err(|error| error.message)?;
+        self.iterations += 1;
+        Ok(format!("iteration {}\n{}", self.iterations, self.show(Some(&position))?))
+    }
+
+    fn step(&mut self) -> Result<String, String>
+    {
+        let rule = self.pivot_rule;
+        let linear_program = self.started()?;
+        let basis = linear_program.basis();
+
+        match linear_program.simplex_iteration(rule)
+        {
+            (SimplexResult::IterationComplete, Some(position)) =>
+            {
+                self.iterations += 1;
+                let leaving = basis[position.row].map_or(String::from("nothing"), |x| format!("x{}", x + 1));
+                Ok(format!("iteration {}: x{} enters, {} leaves\n{}", self.iterations, position.column + 1, leaving, self.show(Some(&position))?))
+            },
+            (SimplexResult::Finished, _) => Ok(format!("optimal, objective {}", linear_program.relative_costs.b_i)),
+            (SimplexResult::Unbound, _) => Ok(String::from("unbounded, no column that can enter has a positive entry")),
+            (SimplexResult::Error(error), _) => Err(error.message),
+            _ => Err(String::from("The pivot rule gave no pivot"))
+        }
+    }
+
+    // the solution is written for the current basis, so a saved tableau reads the same when it is loaded again
+    fn save(&mut self, arguments: &[&str]) -> Result<String, String>
+    {
+        let path = single(arguments, "save FILE")?;
+        let linear_program = self.current_mut()?;
+        linear_program.solution = linear_program.basic_solution();
+
+        let json = serde_json::to_string_pretty(linear_program).map_err(|x| format!("Could not write the tableau: {}", x))?;
+        std::fs::write(path, json + "\n").map_err(|x| format!("Could not write {}: {}", path, x))?;
+        Ok(format!("saved to {}", path))
+    }
+}
+
+fn single<'a>(arguments: &[&'a str], usage: &str) -> Result<&'a str, String>
+{
+    match arguments
+    {
+        [argument] => Ok(argument),
+        _ => Err(format!("Expected {}", usage))
+    }
+}
+
+fn numbers(arguments: &[&str]) -> Result<Vec<f32>, String>
+{
+    arguments.iter().map(|x| x.parse::<f32>().map_err(|_| format!("{} is not a number", x))).collect()
+}
+
+fn row_number(text: &str, rows: usize) -> Result<usize, String>
+{
+    match text.parse::<usize>()
+    {
+        Ok(row) if row >= 1 && row <= rows => Ok(row - 1),
+        _ => Err(format!("{} is not a row, the tableau has rows 1 to {}", text, rows))
+    }
+}
+
+fn column_number(text: &str, columns: usize) -> Result<usize, String>
+{
+    match text.trim_start_matches(['x', 'X']).parse::<usize>()
+    {
+        Ok(column) if column >= 1 && column <= columns => Ok(column - 1),
+        _ => Err(format!("{} is not a column, the tableau has x1 to x{}", text, columns))
+    }
+}
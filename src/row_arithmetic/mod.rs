@@ -1,40 +1,170 @@
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Display;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 // use serde::{Serialize, Deserialize};
 use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::error::{ErrorCode, SimplexError};
 // use serde_json;
 
+// how many optimal bases enumerate_optimal_solutions looks at before it gives up on finding more vertices
+const MAX_OPTIMAL_BASES: usize = 10_000;
 
 pub enum SimplexResult
 {
     Unbound,
     Finished,
     IterationComplete,
-    Error(String)
+    LimitReached(Limit),
+    Error(SimplexError)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Limit
+{
+    Iterations,
+    Time,
+    Cancelled,
+    // the server ran out of its shutdown grace period before the solve finished
+    Shutdown
+}
+
+// checked before every pivot, the tableau is left at the last basis reached which is the best one so far
+#[derive(Debug, Clone, Default)]
+pub struct SolveLimits
+{
+    pub max_iterations: Option<usize>,
+    pub time_limit: Option<Duration>,
+    pub cancelled: Option<Arc<AtomicBool>>,
+    pub shutdown: Option<Arc<AtomicBool>>
+}
+
+impl SolveLimits
+{
+    pub fn reached(&self, iterations: usize, started: &Instant) -> Option<Limit>
+    {
+        if let Some(shutdown) = &self.shutdown
+        {
+            if shutdown.load(Ordering::Relaxed)
+            {
+                return Some(Limit::Shutdown);
+            }
+        }
+
+        if let Some(cancelled) = &self.cancelled
+        {
+            if cancelled.load(Ordering::Relaxed)
+            {
+                return Some(Limit::Cancelled);
+            }
+        }
+
+        if let Some(max_iterations) = self.max_iterations
+        {
+            if iterations >= max_iterations
+            {
+                return Some(Limit::Iterations);
+            }
+        }
+
+        match self.time_limit
+        {
+            Some(time_limit) if started.elapsed() >= time_limit => Some(Limit::Time),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, FromFormField, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum PivotRule
+{
+    // first improving column that has a leaving row, leaving row picked lexicographically
+    Lexicographic,
+    // smallest improving column, ties in the ratio test go to the smallest basic variable
+    Bland,
+    // most negative relative cost, ties in the ratio test go to the smallest basic variable, can cycle
+    Dantzig
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SimplexStatistics
+{
+    pub iterations: usize,
+    pub degenerate_pivots: usize,
+    pub cycling_detected: bool,
+    // the rule in use when the solve stopped, this is Bland when cycling was detected
+    pub pivot_rule: PivotRule
+}
+
+impl SimplexStatistics
+{
+    pub fn new(pivot_rule: PivotRule) -> Self
+    {
+        SimplexStatistics { iterations: 0, degenerate_pivots: 0, cycling_detected: false, pivot_rule: pivot_rule }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct Row
 {
+    #[serde(deserialize_with = "crate::limits::deserialize_entries")]
+    #[schemars(with = "Vec<f32>")]
     pub a_ij: Vec<f32>,
     pub b_i: f32
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct LinearProgram
 {
+    #[serde(deserialize_with = "crate::limits::deserialize_rows")]
+    #[schemars(with = "Vec<Row>")]
     pub tableau: Vec<Row>,
+    #[serde(deserialize_with = "crate::limits::deserialize_costs")]
+    #[schemars(with = "Vec<f32>")]
     pub costs: Vec<f32>,
     pub relative_costs: Row,
+    #[serde(deserialize_with = "crate::limits::deserialize_solution")]
+    #[schemars(with = "Vec<f32>")]
     pub solution: Vec<f32>
 }
 
-struct Position
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct Position
 {
     pub row: usize,
     pub column: usize,
 }
 
+// snapshot of the tableau before a pivot, pivot is None for the final tableau
+#[derive(Debug, Serialize, Clone)]
+pub struct TraceStep
+{
+    pub tableau: Vec<Row>,
+    pub relative_costs: Row,
+    pub basis: Vec<Option<usize>>,
+    pub pivot: Option<Position>
+}
+
+// sent to an observer after every pivot
+#[derive(Debug, Serialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct IterationEvent
+{
+    pub iteration: usize,
+    pub entering: usize,
+    pub leaving: Option<usize>,
+    pub pivot: Position,
+    pub objective: f32,
+    pub degenerate: bool
+}
+
 impl Position
 {
     pub fn new(new_row: usize, new_column: usize) -> Self
@@ -52,24 +182,27 @@ impl Row
         Row{a_ij: cost_changes, b_i: -total_cost}
     }
 
-    pub fn reduce_row(&mut self, minuend: &Row, column: usize) -> Result<bool, String>
+    pub fn reduce_row(&mut self, minuend: &Row, column: usize) -> Result<bool, SimplexError>
     {
         if self.a_ij.len() != minuend.a_ij.len()
         {
-            return Err(format!("Rows cannot be reduced if they are not the same length: subtrahend length = {}, minuend length = {}", self.a_ij.len(), minuend.a_ij.len()));
+            return Err(SimplexError::new(ErrorCode::RowLengthMismatch, 
+                format!("Rows cannot be reduced if they are not the same length: subtrahend length = {}, minuend length = {}", self.a_ij.len(), minuend.a_ij.len()))
+                .with_lengths(minuend.a_ij.len(), self.a_ij.len()));
         }
 
         if column >= self.a_ij.len()
         {
-            return Err(format!("Column cannot be outside of row: row length = {}, column = {}", self.a_ij.len(), column));
+            return Err(SimplexError::new(ErrorCode::ColumnOutOfRange, format!("Column cannot be outside of row: row length = {}, column = {}", self.a_ij.len(), column))
+                .with_column(column));
         }
 
         if minuend.a_ij[column] == 0.0
         {
-            return Err(format!("Cannot reduce row by column if the minuend has a 0 in it"));
+            return Err(SimplexError::new(ErrorCode::ZeroPivot, format!("Cannot reduce row by column if the minuend has a 0 in it")).with_column(column));
         }
 
-        let multiplier = self.determine_how_much_to_multiply_by(&minuend, column);
+        let multiplier = self.determine_how_much_to_multiply_by(minuend, column);
 
         let mut minuend_column: usize = 0;
         while minuend_column < self.a_ij.len()
@@ -95,28 +228,49 @@ impl Row
         -(self.a_ij[column] / minuend.a_ij[column])
     }
 
-    pub fn reduce_row_till_column_one(&mut self, column: usize) -> Result<bool, String>
+    pub fn reduce_row_till_column_one(&mut self, column: usize) -> Result<bool, SimplexError>
     {
         if column >= self.a_ij.len()
         {
-            return Err(format!("The column which is to be set to 1 cannot be the last column or outside of the length of the row: [column = {}, row = {}]", column, self.a_ij.len()));
+            return Err(SimplexError::new(ErrorCode::ColumnOutOfRange, 
+                format!("The column which is to be set to 1 cannot be the last column or outside of the length of the row: [column = {}, row = {}]", column, self.a_ij.len()))
+                .with_column(column));
         }
 
         let multiplier = 1.0 / &self.a_ij[column];
 
         for number in &mut self.a_ij
         {
-            *number = *number * multiplier;
+            *number *= multiplier;
         }
 
         self.a_ij[column] = 1.0;
 
-        self.b_i = self.b_i * multiplier;
+        self.b_i *= multiplier;
 
         Ok(true)
     }
 }
 
+impl TraceStep
+{
+    // the basic feasible solution belonging to this tableau, non-basic variables are 0
+    pub fn solution(&self) -> Vec<f32>
+    {
+        let mut solution = vec![0.0; self.relative_costs.a_ij.len()];
+
+        for (row_index, column) in self.basis.iter().enumerate()
+        {
+            if let Some(column) = column
+            {
+                solution[*column] = self.tableau[row_index].b_i;
+            }
+        }
+
+        solution
+    }
+}
+
 impl Display for Row
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -124,6 +278,9 @@ impl Display for Row
     }
 }
 
+// called with the event and the tableau after every pivot
+type Observer<'a> = &'a mut dyn FnMut(&IterationEvent, &LinearProgram);
+
 #[allow(dead_code)]
 impl LinearProgram
 {
@@ -136,12 +293,12 @@ impl LinearProgram
     //     }
     // }
     // // add logic here
-    pub fn new(json: &String) -> Result<LinearProgram, String>
+    pub fn new(json: &String) -> Result<LinearProgram, SimplexError>
     {
-        let mut linear_program : LinearProgram = match serde_json::from_str(&json)
+        let mut linear_program : LinearProgram = match serde_json::from_str(json)
         {
             Ok(program) => program,
-            Err(error) => return Err(format!("Error while parsing json string as object: {}", error))
+            Err(error) => return Err(SimplexError::new(ErrorCode::InvalidJson, format!("Error while parsing json string as object: {}", error)))
         };
 
         perform_checks(&linear_program)?;
@@ -151,92 +308,96 @@ impl LinearProgram
         Ok(linear_program)
     }
 
-    fn check_row_length (&self) -> Result<bool, String>
+    pub(crate) fn check_row_length(&self) -> Result<(), SimplexError>
     {
-        let mut first_row_length: Option<usize> = None;
-        let mut same_length = true;
-        for length in self.tableau.iter().map(|row| row.a_ij.len())
+        let row_length = match self.tableau.first()
         {
-            match first_row_length
-            {
-                None => first_row_length = Some(length),
-                Some(row_length) => 
-                {
-                    if row_length != length
-                    {
-                        same_length = false;
-                    }
-                }
-            }
+            Some(row) => row.a_ij.len(),
+            None => return Err(SimplexError::new(ErrorCode::EmptyTableau, format!("First row does not have a length")))
         };
 
-        match first_row_length 
+        let mismatch = |found: usize| SimplexError::new(ErrorCode::RowLengthMismatch, format!("Json passed did not have the same length of rows"))
+            .with_lengths(row_length, found);
+
+        if let Some((row_index, row)) = self.tableau.iter().enumerate().find(|(_, row)| row.a_ij.len() != row_length)
+        {
+            return Err(mismatch(row.a_ij.len()).with_row(row_index));
+        }
+
+        for (field, length) in [("costs", self.costs.len()), ("relative_costs", self.relative_costs.a_ij.len()), ("solution", self.solution.len())]
         {
-            None => return Err(format!("First row does no have length")),
-            Some(row_length) =>
+            if length != row_length
             {
-                if row_length != self.costs.len() || row_length != self.relative_costs.a_ij.len() || row_length != self.solution.len()
-                {
-                    same_length = false
-                }
+                return Err(mismatch(length).with_field(field));
             }
         }
 
-        Ok(same_length)
+        Ok(())
     }
 
-    fn check_if_rows_is_greater_than_columns(&self) -> bool
+    fn check_if_rows_is_greater_than_columns(&self) -> Result<(), SimplexError>
     {
-        self.tableau.len() <= self.tableau[0].a_ij.len()
+        if self.tableau.len() <= self.tableau[0].a_ij.len()
+        {
+            return Ok(());
+        }
+
+        Err(SimplexError::new(ErrorCode::TooManyRows, format!("The passed linear program has more columns than rows"))
+            .with_lengths(self.tableau[0].a_ij.len(), self.tableau.len()))
     }
 
-    fn check_if_matrix_starts_with_identity(&self) -> bool
+    fn check_if_matrix_starts_with_identity(&self) -> Result<(), SimplexError>
     {
-        let mut starts_with_identity = true;
-
-        let mut current_row: usize = 0;
-        let max_row = self.tableau.len();
-
-        for row in &self.tableau
+        for (row_index, row) in self.tableau.iter().enumerate()
         {
-            let mut current_column: usize = 0;
-            for column in &row.a_ij
+            for (column_index, value) in row.a_ij.iter().enumerate().take(self.tableau.len())
             {
-                if current_column < max_row
+                let expected = if column_index == row_index { 1.0 } else { 0.0 };
+                if *value != expected
                 {
-                    if current_column == current_row && *column != 1.0
-                    {
-                        starts_with_identity = false;
-                    }
-                    if current_column != current_row && *column != 0.0
-                    {
-                        starts_with_identity = false;
-                    }
+                    return Err(SimplexError::new(ErrorCode::MissingIdentity, format!("The passed linear problem does not start with an identity"))
+                        .with_row(row_index).with_column(column_index).with_value(*value));
                 }
-                current_column += 1;
             }
-            current_row += 1;
         }
 
-        starts_with_identity
+        Ok(())
     }
 
-    fn check_if_solution_is_feasible(&self) -> bool
+    fn check_if_solution_is_feasible(&self) -> Result<(), SimplexError>
     {
-        self.solution.iter().filter(|x| **x < 0.0).count() == 0
+        match self.solution.iter().position(|x| *x < 0.0)
+        {
+            Some(column) => Err(SimplexError::new(ErrorCode::InfeasibleSolution, format!("The passed solution is not feasible"))
+                .with_field("solution").with_column(column).with_value(self.solution[column])),
+            None => Ok(())
+        }
     }
     
-    fn check_if_the_first_m_are_basic(&self) -> bool
+    fn check_if_the_first_m_are_basic(&self) -> Result<(), SimplexError>
     {
-        self.solution.iter().take(self.tableau.len()).count() == self.tableau.len() && 
-        self.solution.iter().skip(self.tableau.len()).filter(|x| **x > 0.0).count() == 0
+        if self.solution.len() < self.tableau.len()
+        {
+            return Err(SimplexError::new(ErrorCode::SolutionNotBasic, format!("The passed solution is not basic or non-degenerate"))
+                .with_field("solution").with_lengths(self.tableau.len(), self.solution.len()));
+        }
+
+        match self.solution.iter().enumerate().skip(self.tableau.len()).find(|(_, x)| **x > 0.0)
+        {
+            Some((column, value)) => Err(SimplexError::new(ErrorCode::SolutionNotBasic, format!("The passed solution is not basic or non-degenerate"))
+                .with_field("solution").with_column(column).with_value(*value)),
+            None => Ok(())
+        }
     }
 
-    fn check_if_b_and_solutions_are_same(&self) -> bool
+    fn check_if_b_and_solutions_are_same(&self) -> Result<(), SimplexError>
     {
-        self.solution.iter().take(self.tableau.len())
-            .zip(self.tableau.iter().map(|x| x.b_i))
-            .filter(|(x, y)| *x == y).count() == self.tableau.len()
+        match self.tableau.iter().enumerate().find(|(row_index, row)| self.solution[*row_index] != row.b_i)
+        {
+            Some((row_index, _)) => Err(SimplexError::new(ErrorCode::SolutionMismatch, format!("Vector b and solution do not align"))
+                .with_field("solution").with_row(row_index).with_column(row_index).with_value(self.solution[row_index])),
+            None => Ok(())
+        }
     }
 
     pub fn calculate_costs(&self) -> Row
@@ -247,14 +408,15 @@ impl LinearProgram
             , total_cost)      
     }
 
-    pub fn find_lexicographically_lowest_row(&self, divider_column: usize) -> Result<usize, String>
+    pub fn find_lexicographically_lowest_row(&self, divider_column: usize) -> Result<usize, SimplexError>
     {
-        let rows: Vec<Row> = self.tableau.iter().map(|x| x.clone()).collect();
+        let rows: Vec<Row> = self.tableau.to_vec();
 
         let mut current_lowest_row: usize = match find_first_row_with_positive_a(&rows, 0, divider_column)
         {
             Ok(number) => number,
-            Err(_) => return Err(format!("There does not exist a row with positive elements in column {}", divider_column))
+            Err(_) => return Err(SimplexError::new(ErrorCode::NoPositiveEntry, format!("There does not exist a row with positive elements in column {}", divider_column))
+                .with_column(divider_column))
         };
 
         let mut current_comparing_row: usize = match find_first_row_with_positive_a(&rows, current_lowest_row + 1, divider_column)
@@ -290,7 +452,7 @@ impl LinearProgram
             .map(|(x, _)| x).collect()
     }
 
-    fn select_row_to_reduce_by(&self, negative_indices: &Vec<usize>) -> Result<(usize, usize), String>
+    fn select_row_to_reduce_by(&self, negative_indices: &Vec<usize>) -> Result<(usize, usize), SimplexError>
     {
         for index in negative_indices
         {
@@ -301,68 +463,337 @@ impl LinearProgram
             };
         }
 
-        Err(format!("No viable rows have been found"))
+        Err(SimplexError::new(ErrorCode::NoPositiveEntry, format!("No viable rows have been found")))
     }
 
-    fn simplex_iteration(&mut self) -> SimplexResult
+    // only Bland and Dantzig look at the basis, in the ratio test
+    fn select_pivot_with_rule(&self, rule: PivotRule, basis: &Vec<Option<usize>>) -> Result<Position, SimplexResult>
+    {
+        match rule
+        {
+            PivotRule::Lexicographic => self.select_pivot(),
+            PivotRule::Bland => 
+            {
+                match self.get_all_negative_cost_rows().first()
+                {
+                    Some(column) => self.select_row_by_smallest_ratio(*column, basis),
+                    None => Err(SimplexResult::Finished)
+                }
+            },
+            PivotRule::Dantzig => 
+            {
+                let most_negative = self.get_all_negative_cost_rows().into_iter()
+                    .min_by(|x, y| self.relative_costs.a_ij[*x].partial_cmp(&self.relative_costs.a_ij[*y]).unwrap_or(std::cmp::Ordering::Equal));
+
+                match most_negative
+                {
+                    Some(column) => self.select_row_by_smallest_ratio(column, basis),
+                    None => Err(SimplexResult::Finished)
+                }
+            }
+        }
+    }
+
+    // ratio test where ties go to the row whose basic variable has the smallest index
+    fn select_row_by_smallest_ratio(&self, column: usize, basis: &Vec<Option<usize>>) -> Result<Position, SimplexResult>
+    {
+        let mut best: Option<(usize, f32)> = None;
+
+        for (row_index, row) in self.tableau.iter().enumerate().filter(|(_, row)| row.a_ij[column] > 0.0)
+        {
+            let ratio = row.b_i / row.a_ij[column];
+
+            best = match best
+            {
+                None => Some((row_index, ratio)),
+                Some((best_row, best_ratio)) =>
+                {
+                    if ratio < best_ratio || (ratio == best_ratio && basis[row_index].unwrap_or(usize::MAX) < basis[best_row].unwrap_or(usize::MAX))
+                    {
+                        Some((row_index, ratio))
+                    }
+                    else 
+                    {
+                        Some((best_row, best_ratio))
+                    }
+                }
+            };
+        }
+
+        match best
+        {
+            Some((row, _)) => Ok(Position::new(row, column)),
+            None => Err(SimplexResult::Unbound)
+        }
+    }
+
+    fn select_pivot(&self) -> Result<Position, SimplexResult>
     {
         let negative_indices = self.get_all_negative_cost_rows();
 
         if negative_indices.len() == 0
         {
-            return SimplexResult::Finished;
+            return Err(SimplexResult::Finished);
         }
-        else 
+
+        match self.select_row_to_reduce_by(&negative_indices)
         {
-            let (reduction_row, column) = match self.select_row_to_reduce_by(&negative_indices)
+            Ok((row, column)) => Ok(Position::new(row, column)),
+            Err(_) => Err(SimplexResult::Unbound)
+        }
+    }
+
+    pub fn pivot(&mut self, position: &Position) -> Result<bool, SimplexError>
+    {
+        if position.row >= self.tableau.len()
+        {
+            return Err(SimplexError::new(ErrorCode::RowOutOfRange, format!("Pivot row is outside of the tableau: [row = {}, rows = {}]", position.row, self.tableau.len()))
+                .with_row(position.row));
+        }
+
+        self.tableau[position.row].reduce_row_till_column_one(position.column)?;
+
+        let cloned_reduction_row = self.tableau[position.row].clone();
+
+        for row in self.tableau.iter_mut().enumerate()
+            .filter(|(x, _)| *x != position.row).map(|(_, y)| y)
+        {
+            row.reduce_row(&cloned_reduction_row, position.column)?;
+        }
+
+        self.relative_costs.reduce_row(&cloned_reduction_row, position.column)?;
+
+        Ok(true)
+    }
+
+    // a single pivot by the rule and where it was, without the cycling check and the limits of a whole solve
+    pub fn simplex_iteration(&mut self, rule: PivotRule) -> (SimplexResult, Option<Position>)
+    {
+        let position = match self.select_pivot_with_rule(rule, &self.basis())
+        {
+            Ok(position) => position,
+            Err(result) => return (result, None)
+        };
+
+        match self.pivot(&position)
+        {
+            Ok(_) => (SimplexResult::IterationComplete, Some(position)),
+            Err(error) => (SimplexResult::Error(error), Some(position))
+        }
+    }
+
+    pub fn preform_simplex(&mut self) -> SimplexResult
+    {
+        self.preform_simplex_with_rule(PivotRule::Lexicographic).0
+    }
+
+    pub fn preform_simplex_with_rule(&mut self, rule: PivotRule) -> (SimplexResult, SimplexStatistics)
+    {
+        self.run_simplex(rule, &SolveLimits::default(), None, None)
+    }
+
+    pub fn preform_simplex_with_limits(&mut self, rule: PivotRule, limits: &SolveLimits) -> (SimplexResult, SimplexStatistics)
+    {
+        self.run_simplex(rule, limits, None, None)
+    }
+
+    // the observer is called with the event and the tableau after every pivot
+    pub fn preform_simplex_observed(&mut self, rule: PivotRule, limits: &SolveLimits, observer: &mut dyn FnMut(&IterationEvent, &LinearProgram)) -> (SimplexResult, SimplexStatistics)
+    {
+        self.run_simplex(rule, limits, None, Some(observer))
+    }
+
+    // same as preform_simplex but keeps a snapshot of every tableau together with the chosen pivot
+    pub fn preform_simplex_with_trace(&mut self, limits: &SolveLimits) -> (SimplexResult, Vec<TraceStep>)
+    {
+        let mut steps: Vec<TraceStep> = Vec::new();
+        let (result, _) = self.run_simplex(PivotRule::Lexicographic, limits, Some(&mut steps), None);
+        (result, steps)
+    }
+
+    // a basis showing up a second time means the rule is cycling, Bland's rule cannot cycle so we switch to it. the lexicographic
+    // rule cannot cycle either, so only Dantzig's rule has its bases remembered. the basis is worked out once and then kept up
+    // to date by the pivots, the entering column takes over the row of the pivot
+    fn run_simplex(&mut self, rule: PivotRule, limits: &SolveLimits, mut trace: Option<&mut Vec<TraceStep>>, mut observer: Option<Observer>) -> (SimplexResult, SimplexStatistics)
+    {
+        let span = tracing::debug_span!("simplex", rows = self.tableau.len(), columns = self.costs.len(), pivot_rule = ?rule);
+        let _entered = span.enter();
+        // checked once instead of on every pivot
+        let log_pivots = tracing::enabled!(tracing::Level::DEBUG);
+
+        let started = Instant::now();
+        let mut statistics = SimplexStatistics::new(rule);
+        let mut visited_bases: HashSet<Vec<Option<usize>>> = HashSet::new();
+        let mut basis = self.basis();
+
+        loop 
+        {
+            if statistics.pivot_rule == PivotRule::Dantzig && !visited_bases.insert(sorted_basis(basis.clone()))
             {
-                Ok(row_result) => row_result,
-                Err(_) => return SimplexResult::Unbound
-            };
+                statistics.cycling_detected = true;
+                statistics.pivot_rule = PivotRule::Bland;
+                tracing::warn!(iteration = statistics.iterations, "the basis repeated, switching to Bland's rule");
+            }
 
-            match self.tableau[reduction_row].reduce_row_till_column_one(column)
+            let position = match self.select_pivot_with_rule(statistics.pivot_rule, &basis)
             {
-                Ok(_) => (),
-                Err(error) => return SimplexResult::Error(error),
+                Ok(position) => position,
+                Err(result) => 
+                {
+                    if let Some(steps) = trace.as_mut()
+                    {
+                        steps.push(self.trace_step(None));
+                    }
+                    return (result, statistics);
+                }
             };
 
-            let cloned_reduction_row = self.tableau[reduction_row].clone();
-
-            for row in self.tableau.iter_mut().enumerate()
-                .filter(|(x, _)| *x != reduction_row).map(|(_, y)| y)
+            if let Some(limit) = limits.reached(statistics.iterations, &started)
             {
-                match row.reduce_row(&cloned_reduction_row, column)
+                if let Some(steps) = trace.as_mut()
                 {
-                    Ok(_) => (),
-                    Err(error) => return SimplexResult::Error(error),
-                };
+                    steps.push(self.trace_step(None));
+                }
+                return (SimplexResult::LimitReached(limit), statistics);
+            }
+
+            if let Some(steps) = trace.as_mut()
+            {
+                steps.push(self.trace_step(Some(position)));
+            }
+
+            // a pivot on a row with b_i = 0 changes the basis but not the vertex
+            let degenerate = self.tableau[position.row].b_i == 0.0;
+            if degenerate
+            {
+                statistics.degenerate_pivots += 1;
             }
 
-            match self.relative_costs.reduce_row(&cloned_reduction_row, column)
+            let leaving = basis[position.row];
+
+            match self.pivot(&position)
             {
-                Ok(_) => (),
-                Err(error) => return SimplexResult::Error(error),
+                Ok(_) => statistics.iterations += 1,
+                Err(error) => return (SimplexResult::Error(error), statistics)
             };
+            basis[position.row] = Some(position.column);
+
+            if log_pivots
+            {
+                tracing::debug!(iteration = statistics.iterations, row = position.row, column = position.column, leaving = ?leaving, degenerate,
+                    objective = self.relative_costs.b_i, elapsed_us = started.elapsed().as_micros() as u64, "pivot");
+            }
 
-            SimplexResult::IterationComplete
+            if let Some(observer) = observer.as_mut()
+            {
+                let event = IterationEvent 
+                { 
+                    iteration: statistics.iterations, entering: position.column, leaving: leaving, 
+                    pivot: position, objective: self.relative_costs.b_i, degenerate: degenerate 
+                };
+                observer(&event, self);
+            }
         }
     }
 
-    pub fn preform_simplex(&mut self) -> SimplexResult
+    // the solution of the current basis, unlike set_solution this works before the optimum is reached
+    pub fn basic_solution(&self) -> Vec<f32>
     {
-        loop 
+        self.trace_step(None).solution()
+    }
+
+    pub fn trace_step(&self, pivot: Option<Position>) -> TraceStep
+    {
+        TraceStep { tableau: self.tableau.clone(), relative_costs: self.relative_costs.clone(), basis: self.basis(), pivot: pivot }
+    }
+
+    // for every row the column which is a unit vector with its 1 in that row and has relative cost 0
+    pub fn basis(&self) -> Vec<Option<usize>>
+    {
+        (0..self.tableau.len()).map(|row_index| 
+        {
+            (0..self.relative_costs.a_ij.len()).find(|column| 
+            {
+                self.relative_costs.a_ij[*column] == 0.0 &&
+                self.tableau.iter().enumerate().all(|(other_index, row)| 
+                {
+                    if other_index == row_index { row.a_ij.get(*column) == Some(&1.0) } else { row.a_ij.get(*column) == Some(&0.0) }
+                })
+            })
+        }).collect()
+    }
+
+    // non-basic columns with a relative cost of 0, at the optimum pivoting on one of them does not change the objective
+    pub fn alternative_optimum_columns(&self) -> Vec<usize>
+    {
+        let basis = self.basis();
+
+        self.relative_costs.a_ij.iter().enumerate()
+            .filter(|(column, cost)| cost.abs() <= 1e-6 && !basis.contains(&Some(*column)))
+            .map(|(column, _)| column).collect()
+    }
+
+    // a zero cost column only leads to another optimum when pivoting on it moves, a degenerate pivot stays on the same
+    // vertex. a column without a positive entry is a ray of optimal solutions
+    pub fn has_multiple_optima(&self) -> bool
+    {
+        self.alternative_optimum_columns().iter().any(|column| match self.find_lexicographically_lowest_row(*column)
+        {
+            Ok(row) => self.tableau[row].b_i.abs() > 1e-6,
+            Err(error) => error.code == ErrorCode::NoPositiveEntry
+        })
+    }
+
+    // breadth first search over the optimal bases reachable by pivoting on zero cost columns, stops after limit solutions.
+    // a degenerate optimal face can have far more bases than vertices, so the bases tried are capped as well and the
+    // limits are checked before every pivot, counted from the start of the search
+    pub fn enumerate_optimal_solutions(&self, limit: usize, limits: &SolveLimits) -> Result<Vec<Vec<f32>>, SimplexError>
+    {
+        let started = Instant::now();
+        let mut pivots = 0;
+        let mut solutions: Vec<Vec<f32>> = vec![self.trace_step(None).solution()];
+        let mut visited_bases: HashSet<Vec<Option<usize>>> = HashSet::from([sorted_basis(self.basis())]);
+        let mut queue: VecDeque<LinearProgram> = VecDeque::from(vec![self.clone()]);
+
+        while let Some(linear_program) = queue.pop_front()
         {
-            match self.simplex_iteration()
+            for column in linear_program.alternative_optimum_columns()
             {
-                SimplexResult::Finished => return SimplexResult::Finished,
-                SimplexResult::Unbound => return SimplexResult::Unbound,
-                SimplexResult::IterationComplete => (),
-                SimplexResult::Error(error) => return SimplexResult::Error(error)
-            } 
+                if solutions.len() >= limit || visited_bases.len() >= MAX_OPTIMAL_BASES || limits.reached(pivots, &started).is_some()
+                {
+                    return Ok(solutions);
+                }
+
+                // without a positive entry the column is a ray of optimal solutions and not another vertex
+                let row = match linear_program.find_lexicographically_lowest_row(column)
+                {
+                    Ok(row) => row,
+                    Err(_) => continue
+                };
+
+                let mut next = linear_program.clone();
+                next.pivot(&Position::new(row, column))?;
+                pivots += 1;
+
+                if !visited_bases.insert(sorted_basis(next.basis()))
+                {
+                    continue;
+                }
+
+                let solution = next.trace_step(None).solution();
+                if !solutions.iter().any(|x| x.iter().zip(solution.iter()).all(|(a, b)| (a - b).abs() <= 1e-6))
+                {
+                    solutions.push(solution);
+                }
+
+                queue.push_back(next);
+            }
         }
+
+        Ok(solutions)
     }
 
-    pub fn set_solution(&mut self) -> Result<String, String>
+    pub fn set_solution(&mut self) -> Result<String, SimplexError>
     {
         for solution in &mut self.solution
         {
@@ -377,7 +808,7 @@ impl LinearProgram
         Ok(format!("Solution set successfully"))
     }
 
-    fn get_all_e_i(&self) -> Result<Vec<Position>, String>
+    fn get_all_e_i(&self) -> Result<Vec<Position>, SimplexError>
     {
         let mut positions: Vec<Position> = Vec::new();
 
@@ -394,7 +825,8 @@ impl LinearProgram
                     }
                     else 
                     {
-                        return Err(format!("A given column with relative cost 0 cannot have more than 1 element greater than 0")); 
+                        return Err(SimplexError::new(ErrorCode::AmbiguousBasis, format!("A given column with relative cost 0 cannot have more than 1 element greater than 0"))
+                            .with_row(row_index).with_column(column_index));
                     }
                 }
             }
@@ -412,8 +844,14 @@ impl Display for LinearProgram
     }
 }
 
+fn sorted_basis(mut basis: Vec<Option<usize>>) -> Vec<Option<usize>>
+{
+    basis.sort();
+    basis
+}
+
 // TODO b needs to be checked first
-fn compare_lexicographic_value_start(row_1: &Row, row_2: &Row, divider_column: usize) -> Result<bool, String>
+fn compare_lexicographic_value_start(row_1: &Row, row_2: &Row, divider_column: usize) -> Result<bool, SimplexError>
 {
     if row_1.b_i / row_1.a_ij[divider_column] == row_2.b_i / row_2.a_ij[divider_column]
     {
@@ -425,11 +863,11 @@ fn compare_lexicographic_value_start(row_1: &Row, row_2: &Row, divider_column: u
     }
 }
 
-fn compare_lexicographic_value(row_1: &Row, row_2: &Row, column: usize, divider_column: usize) -> Result<bool, String>
+fn compare_lexicographic_value(row_1: &Row, row_2: &Row, column: usize, divider_column: usize) -> Result<bool, SimplexError>
 {
     if column >= row_1.a_ij.len()
     {
-        Err(format!("Rows are linearly dependent"))
+        Err(SimplexError::new(ErrorCode::LinearlyDependentRows, format!("Rows are linearly dependent")).with_column(divider_column))
     }
     else 
     {
@@ -444,7 +882,9 @@ fn compare_lexicographic_value(row_1: &Row, row_2: &Row, column: usize, divider_
     }
 }
 
-fn find_first_row_with_positive_a(rows: &Vec<Row>, row: usize, divider_column: usize) -> Result<usize, String>
+// written as !(a > 0) so that a NaN entry is never taken for a positive one
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+fn find_first_row_with_positive_a(rows: &Vec<Row>, row: usize, divider_column: usize) -> Result<usize, SimplexError>
 {
     let mut current_row: usize = row;
     while current_row < rows.len()
@@ -459,40 +899,18 @@ fn find_first_row_with_positive_a(rows: &Vec<Row>, row: usize, divider_column: u
         }
     }
     
-    Err(format!("The next column with positive number in column {} does not exist", row))
+    Err(SimplexError::new(ErrorCode::NoPositiveEntry, format!("The next column with positive number in column {} does not exist", row)).with_column(divider_column))
 }
 
-pub fn perform_checks(linear_program: &LinearProgram) -> Result<String, String>
+// stops at the first check that fails, the error says which row or column it was
+pub fn perform_checks(linear_program: &LinearProgram) -> Result<String, SimplexError>
 {
-    if !linear_program.check_row_length()?
-    {
-        return Err(format!("Json passed did not have the same length of rows"));
-    }
-
-    if !linear_program.check_if_rows_is_greater_than_columns()
-    {
-        return Err(format!("The passed linear program has more columns than rows"))
-    }
-
-    if !linear_program.check_if_matrix_starts_with_identity()
-    {
-        return Err(format!("The passed linear problem does not start with an identity"));
-    }
-
-    if !linear_program.check_if_solution_is_feasible()
-    {
-        return Err(format!("The passed solution is not feasible"));
-    }
-
-    if !linear_program.check_if_the_first_m_are_basic()
-    {
-        return Err(format!("The passed solution is not basic or non-degenerate"));
-    }
-
-    if !linear_program.check_if_b_and_solutions_are_same()
-    {
-        return Err(format!("Vector b and solution do not align"));
-    }
+    linear_program.check_row_length()?;
+    linear_program.check_if_rows_is_greater_than_columns()?;
+    linear_program.check_if_matrix_starts_with_identity()?;
+    linear_program.check_if_solution_is_feasible()?;
+    linear_program.check_if_the_first_m_are_basic()?;
+    linear_program.check_if_b_and_solutions_are_same()?;
 
     Ok(format!("All checks passed"))
-}
\ No newline at end of file
+}
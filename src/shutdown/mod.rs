@@ -0,0 +1,121 @@
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rocket::fairing::AdHoc;
+use rocket::tokio::time::{sleep, Instant};
+
+use crate::auth::ApiKeys;
+use crate::config::JobsConfig;
+use crate::jobs::JobQueue;
+use crate::metrics;
+use crate::store::Store;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Rocket starts closing connections when the grace period is over, the stopped solves get this long to answer before
+const ANSWER_MARGIN: Duration = Duration::from_secs(1);
+
+// draining turns new solves away, stopping ends the ones still running at their next iteration
+pub struct Drain
+{
+    draining: AtomicBool,
+    stopping: Arc<AtomicBool>
+}
+
+impl Drain
+{
+    pub fn new() -> Self
+    {
+        Drain { draining: AtomicBool::new(false), stopping: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn draining(&self) -> bool
+    {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    // handed to every solve as SolveLimits::shutdown
+    pub fn stopping(&self) -> Arc<AtomicBool>
+    {
+        self.stopping.clone()
+    }
+
+    pub fn begin(&self)
+    {
+        self.draining.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop(&self)
+    {
+        self.draining.store(true, Ordering::Relaxed);
+        self.stopping.store(true, Ordering::Relaxed);
+    }
+}
+
+// run_solver has no Rocket state to reach, like the metrics the drain belongs to the process
+pub fn global() -> &'static Drain
+{
+    static DRAIN: OnceLock<Drain> = OnceLock::new();
+    DRAIN.get_or_init(Drain::new)
+}
+
+// Rocket has stopped taking connections by the time this runs, the running solves get the grace period of its shutdown
+// config less ANSWER_MARGIN before they are stopped
+pub fn fairing() -> AdHoc
+{
+    AdHoc::on_shutdown("Drain solves", |rocket| Box::pin(async move
+    {
+        let drain = global();
+        drain.begin();
+
+        let grace = Duration::from_secs(rocket.config().shutdown.grace as u64).saturating_sub(ANSWER_MARGIN);
+        let deadline = Instant::now() + grace;
+        tracing::info!(in_flight = metrics::global().solves_in_flight(), grace_ms = grace.as_millis() as u64, "draining solves");
+
+        while metrics::global().solves_in_flight() > 0 && Instant::now() < deadline
+        {
+            sleep(POLL_INTERVAL).await;
+        }
+
+        let in_flight = metrics::global().solves_in_flight();
+        if in_flight > 0
+        {
+            tracing::warn!(in_flight = in_flight, "stopping the solves still running");
+        }
+        drain.stop();
+
+        let jobs_config: JobsConfig = rocket.figment().extract_inner("jobs").unwrap_or_default();
+        match (rocket.state::<JobQueue>(), rocket.state::<Store>())
+        {
+            (Some(job_queue), Some(store)) if jobs_config.persist => persist_jobs(job_queue.clone(), store.clone()).await,
+            _ => ()
+        };
+
+        // last, so the solves that were stopped have counted themselves
+        if let Some(api_keys) = rocket.state::<ApiKeys>()
+        {
+            let accounts = api_keys.accounts();
+            if let Err(error) = rocket::tokio::task::spawn_blocking(move || accounts.iter().for_each(|account| account.save_usage())).await
+            {
+                tracing::error!(%error, "could not save the usage of the keys");
+            }
+        }
+    }))
+}
+
+// waiting for the stopped jobs and writing them out both block
+async fn persist_jobs(job_queue: JobQueue, store: Store)
+{
+    let persisted = rocket::tokio::task::spawn_blocking(move ||
+    {
+        job_queue.wait_for_running(ANSWER_MARGIN);
+        store.tree("jobs").and_then(|tree| job_queue.persist(&tree))
+    }).await;
+
+    match persisted
+    {
+        Ok(Ok(persisted)) => tracing::info!(jobs = persisted, "kept the queued jobs in the store"),
+        Ok(Err(error)) => tracing::error!(%error, "could not keep the queued jobs"),
+        Err(error) => tracing::error!(%error, "could not keep the queued jobs")
+    };
+}
@@ -1,11 +1,17 @@
 #[cfg(test)]
 mod tests {
     use crate::row_arithmetic::{LinearProgram, Row, perform_checks, self};
-    use crate::LinearProgramResponse;
+    use crate::{LinearProgramResponse, SolutionReport};
+    use crate::error::{ErrorCode, SimplexError};
+    use crate::{parse, render};
+    use crate::{geometry, visualize};
+    use crate::{auth, batch, cache, cli, config, cors, health, jobs, limits, lp_file, metrics, model, mps, openapi, repl, shutdown, store, streaming, validate, SolveOptions};
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
 
     fn simplex_procedure(linear_program: &mut row_arithmetic::LinearProgram) -> LinearProgramResponse
     {
-        match perform_checks(&linear_program)
+        match perform_checks(linear_program)
         {
             Ok(_) => (),
             Err(error) => return LinearProgramResponse::Error(error)
@@ -13,11 +19,14 @@ mod tests {
 
         linear_program.relative_costs = linear_program.calculate_costs();
 
-        match linear_program.preform_simplex()
+        let (result, statistics) = linear_program.preform_simplex_with_rule(row_arithmetic::PivotRule::Lexicographic);
+
+        match result
         {
             row_arithmetic::SimplexResult::Finished => (),
             row_arithmetic::SimplexResult::Unbound => return LinearProgramResponse::Unbound(format!("Problem is unbound and the optimal solution is infinity")),
-            row_arithmetic::SimplexResult::IterationComplete => return LinearProgramResponse::Error(format!("Iteration complete, you should never get this though")),
+            row_arithmetic::SimplexResult::LimitReached(_) => return LinearProgramResponse::Error(SimplexError::internal(format!("The solve was stopped by a limit"))),
+            row_arithmetic::SimplexResult::IterationComplete => return LinearProgramResponse::Error(SimplexError::internal(format!("Iteration complete, you should never get this though"))),
             row_arithmetic::SimplexResult::Error(error) => return LinearProgramResponse::Error(error)
         };
 
@@ -29,7 +38,22 @@ mod tests {
 
         let response_row = row_arithmetic::Row{a_ij: linear_program.solution.clone(), b_i: linear_program.relative_costs.b_i};
 
-        LinearProgramResponse::LinearProgram(response_row)
+        LinearProgramResponse::LinearProgram(SolutionReport::new(response_row, linear_program.has_multiple_optima(), statistics))
+    }
+
+    // maximizes x3 + 2 x4 from the slack basis, the optimum is x4 = 1 with an objective of 2
+    fn example_linear_program() -> LinearProgram
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 1.0},
+                           Row{a_ij: vec![0.0, 1.0, 2.0, 1.0], b_i: 1.0}];
+
+        LinearProgram
+        {
+            tableau: tableau,
+            costs: vec![0.0, 0.0, 1.0, 2.0],
+            relative_costs: Row{a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0},
+            solution: vec![1.0, 1.0, 0.0, 0.0]
+        }
     }
 
     // Put unit test for the simplex method here
@@ -49,8 +73,9 @@ mod tests {
 
         match simplex_procedure(&mut linear_program)
         {
-            LinearProgramResponse::LinearProgram(row) => assert_eq!(row.b_i, 2.0),
+            LinearProgramResponse::LinearProgram(report) => assert_eq!(report.row.b_i, 2.0),
             LinearProgramResponse::Unbound(_) => assert!(false),
+            LinearProgramResponse::LimitReached(_) => assert!(false),
             LinearProgramResponse::Error(_) => assert!(false)
         };
     }
@@ -73,6 +98,7 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(true),
+            LinearProgramResponse::LimitReached(_) => assert!(false),
             LinearProgramResponse::Error(_) => assert!(false)
         };
     }
@@ -95,7 +121,12 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("Json passed did not have the same length of rows", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => 
+            {
+                assert_eq!(error.code, ErrorCode::RowLengthMismatch);
+                assert_eq!((error.details.row, error.details.expected, error.details.found), (Some(1), Some(4), Some(3)));
+            }
         };
     }
 
@@ -117,7 +148,8 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("The passed linear program has more columns than rows", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => assert_eq!(error.code, ErrorCode::TooManyRows)
         };
     }
 
@@ -139,7 +171,12 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("The passed linear problem does not start with an identity", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => 
+            {
+                assert_eq!(error.code, ErrorCode::MissingIdentity);
+                assert_eq!((error.details.row, error.details.column), (Some(0), Some(0)));
+            }
         };
     }
 
@@ -161,7 +198,12 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("The passed solution is not feasible", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => 
+            {
+                assert_eq!(error.code, ErrorCode::InfeasibleSolution);
+                assert_eq!(error.details.column, Some(3));
+            }
         };
     }
 
@@ -183,7 +225,8 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("The passed solution is not basic or non-degenerate", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => assert_eq!(error.code, ErrorCode::SolutionNotBasic)
         };
     }
 
@@ -205,7 +248,773 @@ mod tests {
         {
             LinearProgramResponse::LinearProgram(_) => assert!(false),
             LinearProgramResponse::Unbound(_) => assert!(false),
-            LinearProgramResponse::Error(message) => assert_eq!("Vector b and solution do not align", message)
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(error) => 
+            {
+                assert_eq!(error.code, ErrorCode::SolutionMismatch);
+                assert_eq!(error.details.row, Some(1));
+            }
+        };
+    }
+
+    #[test]
+    fn test_trace_records_pivots_until_optimal()
+    {
+        let mut linear_program = example_linear_program();
+
+        linear_program.relative_costs = linear_program.calculate_costs();
+
+        let (result, steps) = linear_program.preform_simplex_with_trace(&row_arithmetic::SolveLimits::default());
+
+        assert!(matches!(result, row_arithmetic::SimplexResult::Finished));
+        assert_eq!(steps[0].pivot, Some(row_arithmetic::Position{row: 1, column: 2}));
+        assert_eq!(steps[0].basis, vec![Some(0), Some(1)]);
+        assert_eq!(steps.last().unwrap().pivot, None);
+
+        let latex = render::render_trace(&steps, &render::RenderFormat::Latex);
+        assert!(latex.contains("\\boxed{2}"));
+
+        let html = render::render_trace(&steps, &render::RenderFormat::Html);
+        assert!(html.contains("<td class=\"pivot\">2</td>"));
+        assert!(html.contains("<tr class=\"relative-costs\">"));
+    }
+
+    #[test]
+    fn test_feasible_region_of_two_variable_problem()
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 4.0}, 
+                                    Row{a_ij: vec![0.0, 1.0, 1.0, 3.0], b_i: 6.0}];
+
+        let linear_program = LinearProgram
+        {
+            tableau: tableau, 
+            costs: vec![0.0, 0.0, 1.0, 2.0], 
+            relative_costs: {Row{a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0}},
+            solution: vec![4.0, 6.0, 0.0, 0.0]
+        };
+
+        let vertices = geometry::enumerate_vertices(&geometry::halfspaces(&linear_program), 2);
+        assert_eq!(vertices.len(), 4);
+        assert!(vertices.iter().any(|vertex| geometry::same_point(&vertex.point, &vec![3.0, 1.0])));
+
+        let svg = visualize::feasible_region_svg(&linear_program, &row_arithmetic::SolveLimits::default()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<polygon class=\"region\""));
+        assert!(svg.contains("contour optimal"));
+
+        // 200 rows and 3 variables are over a million choices of planes
+        let rows = 200;
+        let too_large = LinearProgram
+        {
+            tableau: (0..rows).map(|row| Row { a_ij: (0..rows + 3).map(|column| if column == row || column >= rows { 1.0 } else { 0.0 }).collect(), b_i: 1.0 }).collect(),
+            costs: vec![0.0; rows + 3],
+            relative_costs: Row { a_ij: vec![0.0; rows + 3], b_i: 0.0 },
+            solution: (0..rows + 3).map(|column| if column < rows { 1.0 } else { 0.0 }).collect()
+        };
+
+        match visualize::feasible_region_svg(&too_large, &row_arithmetic::SolveLimits::default())
+        {
+            Err(error) => assert_eq!(error.code, ErrorCode::TooLarge),
+            _ => assert!(false)
+        };
+    }
+
+    #[test]
+    fn test_multiple_optima_are_detected_and_enumerated()
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 4.0}, 
+                                    Row{a_ij: vec![0.0, 1.0, 1.0, 3.0], b_i: 6.0}];
+
+        let mut linear_program = LinearProgram
+        {
+            tableau: tableau, 
+            costs: vec![0.0, 0.0, 1.0, 1.0], 
+            relative_costs: {Row{a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0}},
+            solution: vec![4.0, 6.0, 0.0, 0.0]
+        };
+
+        let vertices = geometry::polytope_vertices(&linear_program).unwrap();
+        assert_eq!(vertices.len(), 4);
+        assert!(vertices.contains(&vec![0.0, 0.0, 3.0, 1.0]));
+
+        match simplex_procedure(&mut linear_program)
+        {
+            LinearProgramResponse::LinearProgram(report) => 
+            {
+                assert_eq!(report.row.b_i, 4.0);
+                assert!(report.multiple_optima);
+            },
+            LinearProgramResponse::Unbound(_) => assert!(false),
+            LinearProgramResponse::LimitReached(_) => assert!(false),
+            LinearProgramResponse::Error(_) => assert!(false)
+        };
+
+        let solutions = linear_program.enumerate_optimal_solutions(10, &row_arithmetic::SolveLimits::default()).unwrap();
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().all(|x| x[2] + x[3] == 4.0));
+
+        // x3 costs nothing at the optimum but x1 is 0 already, so bringing it in stays on the same vertex
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 0.0},
+                                    Row{a_ij: vec![0.0, 1.0, 1.0, 1.0], b_i: 1.0}];
+
+        let mut degenerate = LinearProgram
+        {
+            tableau: tableau,
+            costs: vec![0.0, 0.0, 0.0, 1.0],
+            relative_costs: Row{a_ij: vec![0.0; 4], b_i: 0.0},
+            solution: vec![0.0, 1.0, 0.0, 0.0]
+        };
+
+        match simplex_procedure(&mut degenerate)
+        {
+            LinearProgramResponse::LinearProgram(report) => assert!(!report.multiple_optima),
+            _ => assert!(false)
+        };
+        assert_eq!(degenerate.enumerate_optimal_solutions(10, &row_arithmetic::SolveLimits::default()).unwrap().len(), 1);
+    }
+
+    // Beale's example cycles with the largest coefficient rule
+    #[test]
+    fn test_cycling_is_detected_and_resolved_with_bland()
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 0.0, 0.25, -8.0, -1.0, 9.0], b_i: 0.0}, 
+                                    Row{a_ij: vec![0.0, 1.0, 0.0, 0.5, -12.0, -0.5, 3.0], b_i: 0.0},
+                                    Row{a_ij: vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0], b_i: 1.0}];
+
+        let linear_program = LinearProgram
+        {
+            tableau: tableau, 
+            costs: vec![0.0, 0.0, 0.0, 0.75, -20.0, 0.5, -6.0], 
+            relative_costs: {Row{a_ij: vec![0.0; 7], b_i: 0.0}},
+            solution: vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]
+        };
+
+        perform_checks(&linear_program).unwrap();
+
+        let mut dantzig = linear_program.clone();
+        dantzig.relative_costs = dantzig.calculate_costs();
+        let (result, statistics) = dantzig.preform_simplex_with_rule(row_arithmetic::PivotRule::Dantzig);
+
+        assert!(matches!(result, row_arithmetic::SimplexResult::Finished));
+        assert!(statistics.cycling_detected);
+        assert_eq!(statistics.pivot_rule, row_arithmetic::PivotRule::Bland);
+        assert!(statistics.degenerate_pivots >= 6);
+        assert!((dantzig.relative_costs.b_i - 1.25).abs() < 1e-5);
+
+        let mut bland = linear_program.clone();
+        bland.relative_costs = bland.calculate_costs();
+        let (result, statistics) = bland.preform_simplex_with_rule(row_arithmetic::PivotRule::Bland);
+
+        assert!(matches!(result, row_arithmetic::SimplexResult::Finished));
+        assert!(!statistics.cycling_detected);
+        assert!((bland.relative_costs.b_i - 1.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_limits_stop_the_solve_at_the_current_basis()
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.0, 1.0, 1.0], b_i: 4.0}, 
+                                    Row{a_ij: vec![0.0, 1.0, 1.0, 3.0], b_i: 6.0}];
+
+        let mut linear_program = LinearProgram
+        {
+            tableau: tableau, 
+            costs: vec![0.0, 0.0, 1.0, 2.0], 
+            relative_costs: {Row{a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0}},
+            solution: vec![4.0, 6.0, 0.0, 0.0]
         };
+
+        linear_program.relative_costs = linear_program.calculate_costs();
+
+        let mut limited = linear_program.clone();
+        let limits = row_arithmetic::SolveLimits { max_iterations: Some(1), time_limit: None, cancelled: None, shutdown: None };
+        let (result, statistics) = limited.preform_simplex_with_limits(row_arithmetic::PivotRule::Lexicographic, &limits);
+
+        assert!(matches!(result, row_arithmetic::SimplexResult::LimitReached(row_arithmetic::Limit::Iterations)));
+        assert_eq!(statistics.iterations, 1);
+        assert_eq!(limited.basic_solution(), vec![0.0, 2.0, 4.0, 0.0]);
+
+        let mut cancelled = linear_program.clone();
+        let limits = row_arithmetic::SolveLimits { max_iterations: None, time_limit: None, cancelled: Some(Arc::new(AtomicBool::new(true))), shutdown: None };
+        let (result, statistics) = cancelled.preform_simplex_with_limits(row_arithmetic::PivotRule::Lexicographic, &limits);
+
+        assert!(matches!(result, row_arithmetic::SimplexResult::LimitReached(row_arithmetic::Limit::Cancelled)));
+        assert_eq!(statistics.iterations, 0);
+    }
+
+    #[test]
+    fn test_job_runs_on_worker_and_reports_result()
+    {
+        let linear_program = example_linear_program();
+
+        let job_queue = jobs::JobQueue::new(&config::JobsConfig::default());
+        let info = job_queue.submit(linear_program, SolveOptions::default(), auth::Caller::anonymous()).unwrap();
+
+        let mut attempts = 0;
+        while job_queue.info(info.id, &auth::Caller::anonymous()).unwrap().finished_at.is_none() && attempts < 500
+        {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            attempts += 1;
+        }
+
+        let (info, result) = job_queue.result(info.id, &auth::Caller::anonymous()).unwrap();
+        assert_eq!(info.status, jobs::JobStatus::Finished);
+        assert!(info.progress.iterations > 0);
+
+        match result
+        {
+            Some(LinearProgramResponse::LinearProgram(report)) => assert_eq!(report.row.b_i, 2.0),
+            _ => assert!(false)
+        };
+
+        assert!(job_queue.info(info.id + 1, &auth::Caller::anonymous()).is_none());
+    }
+
+    #[test]
+    fn test_persisted_jobs_are_queued_again_with_their_options()
+    {
+        let jobs_config = config::JobsConfig::default();
+        let options = SolveOptions { pivot_rule: row_arithmetic::PivotRule::Bland, ..SolveOptions::default() };
+
+        let stopped = jobs::JobQueue::with_workers(&jobs_config, 0);
+        let kept = stopped.submit(example_linear_program(), options, auth::Caller::anonymous()).unwrap();
+        let cancelled = stopped.submit(example_linear_program(), SolveOptions::default(), auth::Caller::anonymous()).unwrap();
+        stopped.cancel(cancelled.id, &auth::Caller::anonymous()).unwrap();
+
+        let store = store::Store::temporary().unwrap();
+        let tree = store.tree("jobs").unwrap();
+        assert_eq!(stopped.persist(&tree).unwrap(), 1);
+
+        let api_keys = auth::ApiKeys::load(&config::AuthConfig::default(), None).unwrap();
+        let job_queue = jobs::JobQueue::new(&jobs_config);
+        assert_eq!(job_queue.restore(&tree, &api_keys).unwrap(), 1);
+        assert!(tree.is_empty());
+
+        let mut attempts = 0;
+        while job_queue.info(kept.id, &auth::Caller::anonymous()).unwrap().finished_at.is_none() && attempts < 500
+        {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            attempts += 1;
+        }
+
+        let (info, result) = job_queue.result(kept.id, &auth::Caller::anonymous()).unwrap();
+        assert_eq!(info.submitted_at, kept.submitted_at);
+        assert!(job_queue.info(cancelled.id, &auth::Caller::anonymous()).is_none());
+
+        match result
+        {
+            Some(LinearProgramResponse::LinearProgram(report)) =>
+            {
+                assert_eq!(report.row.b_i, 2.0);
+                assert_eq!(report.statistics.pivot_rule, row_arithmetic::PivotRule::Bland);
+            },
+            _ => assert!(false)
+        };
+
+        // with no room in the queue the jobs that did not fit stay kept
+        for _ in 0..3
+        {
+            stopped.submit(example_linear_program(), SolveOptions::default(), auth::Caller::anonymous()).unwrap();
+        }
+        assert_eq!(stopped.persist(&tree).unwrap(), 3);
+
+        let full = jobs::JobQueue::with_workers(&config::JobsConfig { queue_capacity: 1, ..config::JobsConfig::default() }, 0);
+        assert_eq!(full.restore(&tree, &api_keys).unwrap(), 1);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(full.restore(&tree, &api_keys).unwrap(), 0);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_batch_of_scenarios_keeps_order_and_status()
+    {
+        let base = example_linear_program();
+
+        let overrides = vec![
+            batch::ScenarioOverride { costs: None, b: None },
+            batch::ScenarioOverride { costs: None, b: Some(vec![2.0, 2.0]) },
+            batch::ScenarioOverride { costs: Some(vec![0.0, 0.0, 1.0]), b: None },
+            batch::ScenarioOverride { costs: None, b: Some(vec![2.0]) }];
+
+        let batch = batch::BatchRequest::Scenarios { base: base, overrides: overrides };
+        let items = batch::solve_batch(batch, &SolveOptions::default(), 3, &auth::Caller::anonymous());
+
+        assert_eq!(items.iter().map(|x| x.index).collect::<Vec<usize>>(), vec![0, 1, 2, 3]);
+        assert_eq!(items[0].status, batch::BatchItemStatus::Optimal);
+        assert_eq!(items[2].status, batch::BatchItemStatus::Error);
+        assert_eq!(items[3].status, batch::BatchItemStatus::Error);
+
+        match &items[1].response
+        {
+            LinearProgramResponse::LinearProgram(report) => assert_eq!(report.row.b_i, 4.0),
+            _ => assert!(false)
+        };
+    }
+
+    #[test]
+    fn test_store_saves_lists_and_deletes_runs()
+    {
+        let linear_program = example_linear_program();
+
+        let store = store::Store::temporary().unwrap();
+        let result = crate::solve_linear_program(linear_program.clone(), &SolveOptions::default());
+
+        let first = store.save(linear_program.clone(), Some(&result), vec![String::from("tuesday")], None).unwrap();
+        let second = store.save(linear_program.clone(), None, vec![], None).unwrap();
+        // another key's run is left out of the list
+        store.save(linear_program, None, vec![String::from("tuesday")], Some("partner")).unwrap();
+
+        let runs = store.list(None, None, 0, store::DEFAULT_PAGE_SIZE).unwrap();
+        assert_eq!(runs.iter().map(|x| x.id).collect::<Vec<u64>>(), vec![second.id, first.id]);
+        assert_eq!(runs[1].outcome, Some(String::from("LinearProgram")));
+        assert_eq!(store.list(Some("tuesday"), None, 0, store::DEFAULT_PAGE_SIZE).unwrap().len(), 1);
+        assert_eq!(store.list(Some("tuesday"), Some("partner"), 0, store::DEFAULT_PAGE_SIZE).unwrap().len(), 1);
+
+        // pages count only the runs that match
+        assert_eq!(store.list(None, None, 1, 1).unwrap().iter().map(|x| x.id).collect::<Vec<u64>>(), vec![first.id]);
+        assert!(store.list(None, None, 2, 1).unwrap().is_empty());
+
+        let updated = store.update_result(second.id, &result).unwrap().unwrap();
+        assert!(updated.result.is_some());
+        assert_eq!(store.list(None, None, 0, 1).unwrap()[0].outcome, Some(String::from("LinearProgram")));
+
+        assert!(store.delete(first.id).unwrap());
+        assert!(store.get(first.id).unwrap().is_none());
+        assert_eq!(store.list(None, None, 0, store::DEFAULT_PAGE_SIZE).unwrap().len(), 1);
+        assert!(!store.delete(first.id).unwrap());
+    }
+
+    #[test]
+    fn test_cache_serves_equal_problems_and_evicts_least_recent()
+    {
+        let linear_program = example_linear_program();
+
+        let options = SolveOptions::default();
+        let result_cache = cache::ResultCache::new(&config::CacheConfig { capacity: 1, persist: false }, None);
+
+        let key = cache::CacheKey::new(&linear_program, &options);
+        assert!(result_cache.get(&key).is_none());
+        result_cache.insert(key.clone(), &crate::solve_linear_program(linear_program.clone(), &options));
+
+        // -0 and 0 are the same problem, the relative costs do not matter
+        let mut same = linear_program.clone();
+        same.costs[0] = -0.0;
+        same.relative_costs.b_i = 5.0;
+        assert_eq!(cache::CacheKey::new(&same, &options), key);
+
+        match result_cache.get(&cache::CacheKey::new(&same, &options))
+        {
+            Some(LinearProgramResponse::LinearProgram(report)) => assert_eq!(report.row.b_i, 2.0),
+            _ => assert!(false)
+        };
+
+        let bland = SolveOptions { pivot_rule: row_arithmetic::PivotRule::Bland, ..SolveOptions::default() };
+        result_cache.insert(cache::CacheKey::new(&linear_program, &bland), &LinearProgramResponse::Unbound(String::new()));
+        assert!(result_cache.get(&key).is_none());
+
+        let statistics = result_cache.statistics();
+        assert_eq!((statistics.entries, statistics.hits, statistics.misses), (1, 1, 2));
+
+        // keys sharing a hash are kept apart, and a hit makes an entry the most recently used again
+        let result_cache = cache::ResultCache::new(&config::CacheConfig { capacity: 2, persist: false }, None);
+        let colliding = |byte: u8| cache::CacheKey { hash: 7, canonical: vec![byte] };
+        result_cache.insert(colliding(1), &LinearProgramResponse::Unbound(String::from("first")));
+        result_cache.insert(colliding(2), &LinearProgramResponse::Unbound(String::from("second")));
+        assert!(result_cache.get(&colliding(1)).is_some());
+
+        result_cache.insert(colliding(3), &LinearProgramResponse::Unbound(String::from("third")));
+        assert!(result_cache.get(&colliding(2)).is_none());
+        match result_cache.get(&colliding(1))
+        {
+            Some(LinearProgramResponse::Unbound(message)) => assert_eq!(message, "first"),
+            _ => assert!(false)
+        };
+    }
+
+    #[test]
+    fn test_json_errors_name_the_field_and_position()
+    {
+        let missing = r#"{"tableau": [{"a_ij": [1.0, 0.0], "b_i": 1.0}], "costs": [0.0, 1.0], "solution": [1.0, 0.0]}"#;
+
+        let error = parse::parse_json::<LinearProgram>(missing).err().unwrap();
+        let json = error.details.json.unwrap();
+        assert_eq!(error.code, ErrorCode::InvalidJson);
+        assert_eq!((json.category.as_str(), json.expected), ("data", Some(String::from("field `relative_costs`"))));
+        assert_eq!(json.suggestions.len(), 1);
+
+        let quoted = "{\"tableau\": [{\"a_ij\": [1.0, \"0.0\"], \"b_i\": 1.0}]}";
+
+        let json = parse::parse_json::<LinearProgram>(quoted).err().unwrap().details.json.unwrap();
+        assert_eq!(json.path, "tableau[0].a_ij[1]");
+        assert_eq!((json.line, json.column), (1, 33));
+        assert_eq!(json.found, Some(String::from("string \"0.0\"")));
+
+        let decimal_comma = "{\"a_ij\" : [32.0, 29,5, 8.0], \"b_i\": }";
+
+        let json = parse::parse_json::<Row>(decimal_comma).err().unwrap().details.json.unwrap();
+        assert_eq!(json.category, "syntax");
+        assert!(json.suggestions.iter().any(|x| x.starts_with("29,5")));
+    }
+
+    #[test]
+    fn test_validate_reports_every_finding()
+    {
+        let tableau = vec![Row{a_ij: vec![1.0, 0.5, 1.0, 1.0], b_i: 1.0}, 
+                                    Row{a_ij: vec![2.0, 1.0, 2.0, 2.0], b_i: 2.0},
+                                    Row{a_ij: vec![0.0, 0.0, f32::NAN], b_i: 1.0}];
+
+        let linear_program = LinearProgram
+        {
+            tableau: tableau, 
+            costs: vec![0.0, 0.0, 1.0, 2.0], 
+            relative_costs: {Row{a_ij: vec![0.0, 0.0, 0.0, 0.0], b_i: 0.0}},
+            solution: vec![1.0, -1.0, 0.0, 0.0]
+        };
+
+        let report = validate::validate(&linear_program);
+        let codes: Vec<ErrorCode> = report.findings.iter().map(|x| x.error.code).collect();
+
+        assert!(!report.valid);
+        assert_eq!(codes.iter().filter(|x| **x == ErrorCode::MissingIdentity).count(), 5);
+        assert!(codes.contains(&ErrorCode::NonFiniteValue));
+        assert!(codes.contains(&ErrorCode::RowLengthMismatch));
+        assert!(codes.contains(&ErrorCode::InfeasibleSolution));
+        assert!(codes.contains(&ErrorCode::SolutionMismatch));
+        assert!(!codes.contains(&ErrorCode::RankDeficient));
+
+        let mut dependent = linear_program.clone();
+        dependent.tableau.pop();
+
+        let report = validate::validate(&dependent);
+        assert!(report.findings.iter().any(|x| x.error.code == ErrorCode::RankDeficient && x.error.details.found == Some(1)));
+    }
+
+    #[test]
+    fn test_openapi_describes_every_mounted_route()
+    {
+        let rocket = crate::rocket();
+        assert_eq!(openapi::undescribed(rocket.routes()), Vec::<String>::new());
+
+        let document = openapi::document(rocket.routes());
+        assert!(document["paths"]["/jobs/{id}"]["get"]["parameters"][0]["name"] == "id");
+        assert!(document["paths"]["/"]["post"]["requestBody"].is_object());
+        assert!(document["paths"]["/v1/solve"]["post"].is_object() && document["paths"]["/v1/jobs"]["post"].is_object());
+        assert!(document["components"]["schemas"]["LinearProgram"].is_object());
+        assert!(document["components"]["schemas"]["LinearProgramResponse"].is_object());
+    }
+
+    #[test]
+    fn test_cors_echoes_only_allowed_origins()
+    {
+        let mut cors_config = config::CorsConfig::default();
+        assert_eq!(cors::Cors::new(cors_config.clone()).unwrap().allowed_origin("https://example.com"), Some(String::from("*")));
+
+        // a wildcard cannot be sent together with credentials
+        cors_config.allow_credentials = true;
+        assert!(cors::Cors::new(cors_config.clone()).is_err());
+
+        cors_config.allowed_origins = vec![String::from("https://simplex.example.com/")];
+        let cors = cors::Cors::new(cors_config).unwrap();
+        assert_eq!(cors.allowed_origin("https://simplex.example.com"), Some(String::from("https://simplex.example.com")));
+        assert_eq!(cors.allowed_origin("https://evil.example.com"), None);
+    }
+
+    #[test]
+    fn test_api_keys_limit_and_count_each_key()
+    {
+        let linear_program = example_linear_program();
+
+        let store = store::Store::temporary().unwrap();
+        let auth_config = config::AuthConfig { enabled: true, ..config::AuthConfig::default() };
+        let api_keys = auth::ApiKeys::load(&auth_config, Some(&store)).unwrap();
+
+        let key = auth::ApiKey { name: String::from("partner"), key: String::from("secret"), admin: false, requests_per_minute: Some(2), max_rows: Some(2), max_columns: Some(3) };
+        api_keys.add(key).unwrap();
+
+        assert_eq!(api_keys.authenticate_at(None, 1).err().unwrap().code, ErrorCode::Unauthorized);
+        assert_eq!(api_keys.authenticate_at(Some("wrong"), 1).err().unwrap().code, ErrorCode::Unauthorized);
+
+        let caller = api_keys.authenticate_at(Some("secret"), 1).unwrap();
+        let error = caller.check(&linear_program).err().unwrap();
+        assert_eq!(error.code, ErrorCode::TooLarge);
+        assert_eq!((error.details.expected, error.details.found), (Some(3), Some(4)));
+
+        let (response, wall_time) = crate::timed_solve(linear_program, &SolveOptions::default());
+        caller.record(&response, wall_time);
+
+        api_keys.authenticate_at(Some("secret"), 1).unwrap();
+        let error = api_keys.authenticate_at(Some("secret"), 1).err().unwrap();
+        assert_eq!((error.code, error.details.expected, error.details.found), (ErrorCode::RateLimited, Some(2), Some(3)));
+
+        // the count starts over with the next minute
+        api_keys.authenticate_at(Some("secret"), 2).unwrap();
+
+        let usage = &api_keys.usage()[0];
+        assert_eq!((usage.requests, usage.rate_limited, usage.solves), (3, 1, 1));
+        assert_eq!(usage.iterations, auth::iterations(&response));
+
+        // keys added at runtime are read back from the store, the usage as it was written when the minute changed
+        let reloaded = auth::ApiKeys::load(&auth_config, Some(&store)).unwrap();
+        assert_eq!((reloaded.usage()[0].requests, reloaded.usage()[0].solves), (2, 1));
+
+        // and all of it once it is saved at shutdown
+        api_keys.accounts().iter().for_each(|account| account.save_usage());
+        let reloaded = auth::ApiKeys::load(&auth_config, Some(&store)).unwrap();
+        assert_eq!(reloaded.usage(), api_keys.usage());
+
+        // jobs belong to the key that submitted them
+        let job_queue = jobs::JobQueue::new(&config::JobsConfig { workers: 1, ..config::JobsConfig::default() });
+        let job = job_queue.submit(example_linear_program(), SolveOptions::default(), caller.clone()).unwrap();
+        assert!(job_queue.info(job.id, &auth::Caller::anonymous()).is_none() && job_queue.cancel(job.id, &auth::Caller::anonymous()).is_none());
+        assert!(job_queue.info(job.id, &caller).is_some());
+    }
+
+    #[test]
+    fn test_problem_limits_stop_the_parse_at_the_exceeded_limit()
+    {
+        let body = r#"{"tableau": [{"a_ij": [1.0, 0.0, 2.0], "b_i": 1.0}, {"a_ij": [0.0, 1.0, 3.0], "b_i": 1.0}],
+            "costs": [0.0, 0.0, 1.0], "relative_costs": {"a_ij": [0.0, 0.0, 0.0], "b_i": 0.0}, "solution": [1.0, 1.0, 0.0]}"#;
+
+        let generous = config::ProblemLimitsConfig::default();
+        let (parsed, exceeded) = limits::parse_with_limits(&generous, None, || parse::parse_json::<LinearProgram>(body));
+        assert!(parsed.is_ok() && exceeded.is_none());
+
+        let limit_of = |problem_limits: config::ProblemLimitsConfig|
+        {
+            let (parsed, exceeded) = limits::parse_with_limits(&problem_limits, None, || parse::parse_json::<LinearProgram>(body));
+            assert!(parsed.is_err());
+
+            let exceeded = exceeded.unwrap();
+            assert_eq!(exceeded.code, ErrorCode::TooLarge);
+            (exceeded.details.limit.unwrap(), exceeded.details.found)
+        };
+
+        // found is how many there really were, the nonzeros are not all read so they have none
+        assert_eq!(limit_of(config::ProblemLimitsConfig { max_rows: 1, ..generous.clone() }), (String::from("max_rows"), Some(2)));
+        assert_eq!(limit_of(config::ProblemLimitsConfig { max_columns: 2, ..generous.clone() }), (String::from("max_columns"), Some(3)));
+        assert_eq!(limit_of(config::ProblemLimitsConfig { max_nonzeros: 3, ..generous.clone() }), (String::from("max_nonzeros"), None));
+
+        // the costs count against max_columns as well
+        let long_costs = body.replace(r#""costs": [0.0, 0.0, 1.0]"#, r#""costs": [0.0, 0.0, 1.0, 0.0]"#);
+        let (_, exceeded) = limits::parse_with_limits(&config::ProblemLimitsConfig { max_columns: 3, ..generous.clone() }, None, || parse::parse_json::<LinearProgram>(&long_costs));
+        assert_eq!(exceeded.unwrap().details.field, Some(String::from("costs")));
+
+        // a batch is counted while it is read and the error has every problem it held
+        let batch_body = format!("[{}, {}, {}]", body, body, body);
+        let (parsed, exceeded) = limits::parse_with_limits(&generous, Some(2), || parse::parse_json::<batch::BatchRequest>(&batch_body));
+        let exceeded = exceeded.unwrap();
+        assert!(parsed.is_err());
+        assert_eq!((exceeded.details.limit.unwrap(), exceeded.details.expected, exceeded.details.found), (String::from("max_problems"), Some(2), Some(3)));
+
+        // the nonzeros are counted for each problem of a batch on its own
+        let (parsed, _) = limits::parse_with_limits(&config::ProblemLimitsConfig { max_nonzeros: 4, ..generous.clone() }, Some(3), || parse::parse_json::<batch::BatchRequest>(&batch_body));
+        assert!(parsed.is_ok());
+
+        // so are the lists of a scenario override, one left out or null is kept from the base
+        let scenarios = format!(r#"{{"base": {}, "overrides": [{{"costs": null}}, {{"b": [1.0, 2.0, 3.0]}}]}}"#, body);
+        let (parsed, exceeded) = limits::parse_with_limits(&config::ProblemLimitsConfig { max_rows: 2, ..generous.clone() }, Some(3), || parse::parse_json::<batch::BatchRequest>(&scenarios));
+        let exceeded = exceeded.unwrap();
+        assert!(parsed.is_err());
+        assert_eq!((exceeded.details.field.unwrap(), exceeded.details.limit.unwrap(), exceeded.details.found), (String::from("b"), String::from("max_rows"), Some(3)));
+        assert_eq!(parse::parse_json::<batch::BatchRequest>(&scenarios).unwrap().len(), 2);
+
+        // outside of a request nothing is limited
+        assert!(parse::parse_json::<LinearProgram>(body).is_ok());
+    }
+
+    #[test]
+    fn test_metrics_count_solves_in_the_prometheus_format()
+    {
+        let linear_program = example_linear_program();
+
+        let metrics = metrics::Metrics::new();
+        {
+            let _in_flight = metrics.start_solve(&linear_program);
+            assert!(metrics.render().contains("simplex_solves_in_flight 1\n"));
+
+            let response = crate::solve_linear_program(linear_program, &SolveOptions::default());
+            metrics.finish_solve(&response, std::time::Duration::from_millis(3));
+        }
+        metrics.record_request("POST", "/runs/<id>", 200);
+
+        let text = metrics.render();
+        assert!(text.contains("simplex_solves_in_flight 0\n"));
+        assert!(text.contains("simplex_solves_total{outcome=\"optimal\"} 1\n"));
+        assert!(text.contains("simplex_http_requests_total{method=\"POST\",route=\"/runs/<id>\",status=\"200\"} 1\n"));
+        // the buckets are cumulative
+        assert!(text.contains("simplex_solve_duration_seconds_bucket{le=\"0.0025\"} 0\n"));
+        assert!(text.contains("simplex_solve_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("simplex_problem_rows_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("simplex_problem_nonzeros_sum 6\n"));
+    }
+
+    #[test]
+    fn test_readiness_self_test_reaches_the_known_objective()
+    {
+        assert_eq!(health::self_test(), Ok(()));
+
+        let rocket = rocket::build().mount("/", health::routes()).manage(store::Store::temporary().unwrap());
+        let client = rocket::local::blocking::Client::untracked(rocket).unwrap();
+        let response = client.get("/ready").dispatch();
+        assert_eq!(response.status(), rocket::http::Status::Ok);
+
+        let readiness: serde_json::Value = response.into_json().unwrap();
+        assert_eq!(readiness["ready"], true);
+        assert_eq!(readiness["checks"].as_array().unwrap().iter().map(|x| (x["name"].as_str().unwrap(), x["ok"].as_bool().unwrap())).collect::<Vec<_>>(),
+            vec![("solver", true), ("store", true)]);
+
+        let features: Vec<String> = client.get("/version").dispatch().into_json::<serde_json::Value>().unwrap()["features"].as_array().unwrap().iter()
+            .map(|x| String::from(x.as_str().unwrap())).collect();
+        assert!(features.contains(&String::from("cache")) && features.contains(&String::from("persisted_jobs")));
+        assert!(!features.contains(&String::from("api_keys")));
+    }
+
+    #[test]
+    fn test_stopping_the_drain_stops_a_running_solve()
+    {
+        let mut linear_program = example_linear_program();
+        linear_program.relative_costs = linear_program.calculate_costs();
+
+        let drain = shutdown::Drain::new();
+        drain.begin();
+        assert!(drain.draining());
+
+        // the solve still runs while the server is only draining
+        let limits = row_arithmetic::SolveLimits { shutdown: Some(drain.stopping()), ..row_arithmetic::SolveLimits::default() };
+        let (result, _) = linear_program.clone().preform_simplex_with_limits(row_arithmetic::PivotRule::Lexicographic, &limits);
+        assert!(matches!(result, row_arithmetic::SimplexResult::Finished));
+
+        // the solve takes two pivots, stopping after the first one ends it before the second
+        let (result, statistics) = linear_program.preform_simplex_observed(row_arithmetic::PivotRule::Lexicographic, &limits, &mut |_, _| drain.stop());
+        assert!(matches!(result, row_arithmetic::SimplexResult::LimitReached(row_arithmetic::Limit::Shutdown)));
+        assert_eq!(statistics.iterations, 1);
+    }
+
+    #[test]
+    fn test_cli_prints_the_tableau_and_the_result_as_text()
+    {
+        let mut linear_program = example_linear_program();
+        linear_program.relative_costs = linear_program.calculate_costs();
+
+        let text = linear_program.trace_step(Some(row_arithmetic::Position{row: 1, column: 2})).to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "    x1*  x2*   x3  x4   b");
+        assert_eq!(lines[3], "x2    0    1  [2]   1   1");
+        assert!(lines[5].starts_with(" z"));
+
+        let response = crate::run_solver(linear_program.clone(), &SolveOptions::default(), &mut |_, _| ());
+        assert_eq!(cli::exit_code(&response), cli::EXIT_OPTIMAL);
+        assert!(cli::format_table(&response, &model::StandardForm::of(linear_program)).contains("objective   2\n"));
+        assert_eq!(cli::parse_pivot_rule("Bland"), Ok(row_arithmetic::PivotRule::Bland));
+    }
+
+    #[test]
+    fn test_mps_model_in_standard_form_solves_to_its_own_objective()
+    {
+        let text = "NAME          diet\nROWS\n N  cost\n G  c1\n G  c2\nCOLUMNS\n    x  cost  1  c1  1\n    x  c2  3\n    y  cost  1  c1  2\n    y  c2  1\nRHS\n    RHS  c1  4  c2  6\nBOUNDS\n UP BND  y  10\nENDATA\n";
+        let model = mps::read(text).unwrap();
+        assert_eq!(lp_file::read(&lp_file::write(&model)).unwrap().constraints, model.constraints);
+        assert_eq!(mps::read(&mps::write(&model)).unwrap(), model);
+
+        let standard_form = model::to_standard_form(&model, None).unwrap();
+        let m = standard_form.linear_program.tableau.len();
+        for (index, row) in standard_form.linear_program.tableau.iter().enumerate()
+        {
+            assert!((0..m).all(|column| row.a_ij[column] == if column == index { 1.0 } else { 0.0 }));
+        }
+
+        let response = crate::run_solver(standard_form.linear_program.clone(), &SolveOptions::default(), &mut |_, _| ());
+        match response
+        {
+            LinearProgramResponse::LinearProgram(report) =>
+            {
+                assert!((standard_form.objective(&report.row.a_ij) - 2.8).abs() < 1e-4);
+                assert!(!standard_form.artificial_in_use(&report.row.a_ij));
+                let values = standard_form.values(&report.row.a_ij);
+                assert!((values[0].1 - 1.6).abs() < 1e-4 && (values[1].1 - 1.2).abs() < 1e-4);
+            },
+            _ => assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_repl_builds_a_tableau_and_steps_to_the_optimum()
+    {
+        let mut session = repl::Session::default();
+        assert!(session.execute("new 0 0 1 2").is_ok() && session.execute("add-row 0 1 1 1 1").is_ok());
+        // a tableau that failed start is not pivoted on
+        assert!(session.execute("start").is_err());
+        assert!(session.execute("step").is_err() && session.execute("pivot 1 3").is_err() && session.execute("solution").is_err());
+
+        for command in ["new 0 0 1 2", "add-row 1 0 1 1 1", "add-row 0 1 2 1 1", "add-row 1 1 1 1 9", "remove-row 3", "start"]
+        {
+            assert!(session.execute(command).unwrap().is_some());
+        }
+        assert!(session.execute("pivot 2 3").unwrap().unwrap().contains("[1]"));
+        assert!(session.execute("step").unwrap().unwrap().starts_with("iteration 2: x4 enters"));
+        assert_eq!(session.execute("step").unwrap().unwrap(), "optimal, objective 2");
+        assert!(session.execute("costs").unwrap().unwrap().ends_with("the basis is optimal"));
+        assert!(session.execute("pivot 1 9").is_err());
+
+        // a saved tableau that was pivoted loads with its relative costs, one that fails the checks has to pass start first
+        let path = std::env::temp_dir().join(format!("repl_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert!(session.execute(&format!("save {}", path)).is_ok() && session.execute(&format!("load {}", path)).is_ok());
+        assert!(session.execute("costs").unwrap().unwrap().ends_with("the basis is optimal"));
+
+        for command in ["new 0 0 1 2", "add-row 0 1 1 1 1", &format!("save {}", path), &format!("load {}", path)]
+        {
+            assert!(session.execute(command).is_ok());
+        }
+        assert!(session.execute("step").is_err());
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(session.execute("quit").unwrap(), None);
+    }
+
+    #[test]
+    fn test_stream_sends_an_event_per_iteration_and_the_result()
+    {
+        let body = serde_json::to_string(&example_linear_program()).unwrap();
+
+        let rocket = rocket::build().mount("/", streaming::routes()).manage(config::SolverConfig::default());
+        let client = rocket::local::blocking::Client::untracked(rocket).unwrap();
+        let text = client.post("/stream").body(body).dispatch().into_string().unwrap();
+
+        let events: Vec<(&str, serde_json::Value)> = text.split("\n\n").filter(|event| !event.trim().is_empty()).map(|event|
+        {
+            let name = event.lines().find_map(|line| line.strip_prefix("event:")).unwrap().trim();
+            let data = event.lines().find_map(|line| line.strip_prefix("data:")).unwrap().trim();
+            (name, serde_json::from_str(data).unwrap())
+        }).collect();
+
+        let (last, iterations) = events.split_last().unwrap();
+        assert!(!iterations.is_empty() && iterations.iter().all(|(name, _)| *name == "iteration"));
+        assert_eq!(iterations.last().unwrap().1["iteration"], iterations.len());
+
+        assert_eq!(last.0, "result");
+        assert_eq!(last.1["LinearProgram"]["b_i"], 2.0);
+        assert_eq!(last.1["LinearProgram"]["statistics"]["iterations"], iterations.len());
+    }
+
+    #[test]
+    fn test_routes_under_v1_answer_errors_with_the_status_of_their_code()
+    {
+        let body = serde_json::to_string(&example_linear_program()).unwrap();
+
+        // without workers or room in the queue every job is turned away
+        let full = jobs::JobQueue::with_workers(&config::JobsConfig { queue_capacity: 0, ..config::JobsConfig::default() }, 0);
+        let rocket = rocket::build().mount("/", jobs::routes()).mount("/v1", jobs::routes()).manage(full).manage(config::SolverConfig::default());
+        let client = rocket::local::blocking::Client::untracked(rocket).unwrap();
+
+        let response = client.post("/jobs").body(&body).dispatch();
+        assert_eq!(response.status(), rocket::http::Status::ServiceUnavailable);
+        let error = response.into_json::<serde_json::Value>().unwrap();
+        assert_eq!(error["Error"]["code"], serde_json::json!(ErrorCode::QueueFull));
+
+        let response = client.post("/v1/jobs").body(&body).dispatch();
+        assert_eq!(response.status(), rocket::http::Status::ServiceUnavailable);
+        let error = response.into_json::<SimplexError>().unwrap();
+        assert_eq!(error.code, ErrorCode::QueueFull);
     }
-}
\ No newline at end of file
+}
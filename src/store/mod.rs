@@ -0,0 +1,405 @@
+use rocket::http::Status;
+use rocket::serde::{Deserialize, Serialize};
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use crate::auth::Caller;
+use crate::config::{SolverConfig, StoreConfig};
+use crate::logging::RequestId;
+use crate::error::{ErrorCode, SimplexError};
+use crate::parse::JsonBody;
+use crate::row_arithmetic::LinearProgram;
+use crate::v1::RouteError;
+use crate::{LinearProgramResponse, SolveOptions};
+
+// the result is kept as plain json so that runs saved by older versions still load after the response changes
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct StoredRun
+{
+    pub id: u64,
+    // seconds since the unix epoch
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub tags: Vec<String>,
+    // the name of the key that saved the run, runs saved without API keys belong to nobody
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub problem: LinearProgram,
+    pub result: Option<Value>
+}
+
+// kept in a tree of its own next to the run, so listing the runs does not read every problem
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct RunSummary
+{
+    pub id: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    // the variant of the stored LinearProgramResponse, e.g. LinearProgram or Unbound
+    pub outcome: Option<String>
+}
+
+impl StoredRun
+{
+    pub fn summary(&self) -> RunSummary
+    {
+        let outcome = match &self.result
+        {
+            Some(Value::Object(map)) => map.keys().next().cloned(),
+            _ => None
+        };
+
+        RunSummary { id: self.id, created_at: self.created_at, updated_at: self.updated_at, tags: self.tags.clone(), owner: self.owner.clone(), outcome: outcome }
+    }
+}
+
+// how many runs a page of the list has when the request does not say, and the most it may ask for
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+// runs live in a sled tree keyed by their id in big endian so iterating goes from oldest to newest, their summaries in
+// another tree under the same keys. a clone opens the same database
+#[derive(Clone)]
+pub struct Store
+{
+    db: sled::Db,
+    runs: sled::Tree,
+    summaries: sled::Tree,
+    pub save_solves: bool
+}
+
+impl Store
+{
+    pub fn open(config: &StoreConfig) -> Result<Store, String>
+    {
+        match sled::open(&config.path)
+        {
+            Ok(db) => Store::from_db(db, config.save_solves),
+            Err(error) => Err(format!("Could not open the run store at {}: {}", config.path, error))
+        }
+    }
+
+    // deleted again when dropped, for tests
+    #[cfg(test)]
+    pub fn temporary() -> Result<Store, String>
+    {
+        match sled::Config::new().temporary(true).open()
+        {
+            Ok(db) => Store::from_db(db, true),
+            Err(error) => Err(format!("Could not open a temporary run store: {}", error))
+        }
+    }
+
+    fn from_db(db: sled::Db, save_solves: bool) -> Result<Store, String>
+    {
+        let (runs, summaries) = match (db.open_tree("runs"), db.open_tree("run_summaries"))
+        {
+            (Ok(runs), Ok(summaries)) => (runs, summaries),
+            (Err(error), _) | (_, Err(error)) => return Err(format!("Could not open the runs in the store: {}", error))
+        };
+
+        let store = Store { db: db, runs: runs, summaries: summaries, save_solves: save_solves };
+        store.add_missing_summaries()?;
+        Ok(store)
+    }
+
+    // runs saved before the summaries were kept get theirs the first time the store is opened
+    fn add_missing_summaries(&self) -> Result<(), String>
+    {
+        if !self.summaries.is_empty() || self.runs.is_empty()
+        {
+            return Ok(());
+        }
+
+        for entry in self.runs.iter()
+        {
+            let (_, bytes) = entry.map_err(|x| format!("Could not read the stored runs: {}", x))?;
+            self.write_summary(&from_bytes(&bytes)?.summary())?;
+        }
+
+        Ok(())
+    }
+
+    // other parts of the server keep their own data in the same database
+    pub fn tree(&self, name: &str) -> Result<sled::Tree, String>
+    {
+        match self.db.open_tree(name)
+        {
+            Ok(tree) => Ok(tree),
+            Err(error) => Err(format!("Could not open {} in the store: {}", name, error))
+        }
+    }
+
+    // reads from the runs tree so a store that can no longer be read shows up
+    pub fn check(&self) -> Result<(), String>
+    {
+        match self.runs.first()
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Could not read the runs: {}", error))
+        }
+    }
+
+    pub fn save(&self, problem: LinearProgram, result: Option<&LinearProgramResponse>, tags: Vec<String>, owner: Option<&str>) -> Result<StoredRun, String>
+    {
+        let id = match self.db.generate_id()
+        {
+            Ok(id) => id,
+            Err(error) => return Err(format!("Could not generate an id for the run: {}", error))
+        };
+
+        let now = crate::unix_time();
+        let run = StoredRun { id: id, created_at: now, updated_at: now, tags: tags, owner: owner.map(String::from), problem: problem, result: to_value(result)? };
+
+        self.write(&run)?;
+        Ok(run)
+    }
+
+    pub fn get(&self, id: u64) -> Result<Option<StoredRun>, String>
+    {
+        match self.runs.get(id.to_be_bytes())
+        {
+            Ok(Some(bytes)) => from_bytes(&bytes).map(Some),
+            Ok(None) => Ok(None),
+            Err(error) => Err(format!("Could not read run {}: {}", id, error))
+        }
+    }
+
+    // newest first, only the owner's runs and only those carrying the tag if one is given. offset skips that many of the
+    // matching runs and at most limit are returned
+    pub fn list(&self, tag: Option<&str>, owner: Option<&str>, offset: usize, limit: usize) -> Result<Vec<RunSummary>, String>
+    {
+        let mut summaries: Vec<RunSummary> = Vec::new();
+        let mut skipped = 0;
+
+        for entry in self.summaries.iter().rev()
+        {
+            if summaries.len() >= limit
+            {
+                break;
+            }
+
+            let (_, bytes) = match entry
+            {
+                Ok(entry) => entry,
+                Err(error) => return Err(format!("Could not read the stored runs: {}", error))
+            };
+
+            let summary: RunSummary = match serde_json::from_slice(&bytes)
+            {
+                Ok(summary) => summary,
+                Err(error) => return Err(format!("A stored run could not be read: {}", error))
+            };
+
+            if summary.owner.as_deref() != owner || !tag.map_or(true, |tag| summary.tags.iter().any(|x| x == tag))
+            {
+                continue;
+            }
+
+            if skipped < offset
+            {
+                skipped += 1;
+                continue;
+            }
+
+            summaries.push(summary);
+        }
+
+        Ok(summaries)
+    }
+
+    pub fn update_result(&self, id: u64, result: &LinearProgramResponse) -> Result<Option<StoredRun>, String>
+    {
+        let mut run = match self.get(id)?
+        {
+            Some(run) => run,
+            None => return Ok(None)
+        };
+
+        run.result = to_value(Some(result))?;
+        run.updated_at = crate::unix_time();
+
+        self.write(&run)?;
+        Ok(Some(run))
+    }
+
+    pub fn delete(&self, id: u64) -> Result<bool, String>
+    {
+        if let Err(error) = self.summaries.remove(id.to_be_bytes())
+        {
+            return Err(format!("Could not delete run {}: {}", id, error));
+        }
+
+        match self.runs.remove(id.to_be_bytes())
+        {
+            Ok(removed) => Ok(removed.is_some()),
+            Err(error) => Err(format!("Could not delete run {}: {}", id, error))
+        }
+    }
+
+    fn write(&self, run: &StoredRun) -> Result<(), String>
+    {
+        let bytes = match serde_json::to_string(run)
+        {
+            Ok(json) => json.into_bytes(),
+            Err(error) => return Err(format!("Could not serialize run {}: {}", run.id, error))
+        };
+
+        // not flushed here, that would block the handler on the disk. sled writes its log out every half second and
+        // once more when the store is dropped at shutdown
+        match self.runs.insert(run.id.to_be_bytes(), bytes)
+        {
+            Ok(_) => self.write_summary(&run.summary()),
+            Err(error) => Err(format!("Could not write run {}: {}", run.id, error))
+        }
+    }
+
+    fn write_summary(&self, summary: &RunSummary) -> Result<(), String>
+    {
+        let bytes = match serde_json::to_string(summary)
+        {
+            Ok(json) => json.into_bytes(),
+            Err(error) => return Err(format!("Could not serialize run {}: {}", summary.id, error))
+        };
+
+        match self.summaries.insert(summary.id.to_be_bytes(), bytes)
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Could not write run {}: {}", summary.id, error))
+        }
+    }
+}
+
+fn to_value(result: Option<&LinearProgramResponse>) -> Result<Option<Value>, String>
+{
+    match result
+    {
+        Some(result) => match serde_json::to_value(result)
+        {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => Err(format!("Could not serialize the result: {}", error))
+        },
+        None => Ok(None)
+    }
+}
+
+fn from_bytes(bytes: &[u8]) -> Result<StoredRun, String>
+{
+    match serde_json::from_slice(bytes)
+    {
+        Ok(run) => Ok(run),
+        Err(error) => Err(format!("A stored run could not be read: {}", error))
+    }
+}
+
+type StoreResult<T> = Result<T, RouteError>;
+
+fn internal_error(error: String) -> RouteError
+{
+    RouteError::new(Status::InternalServerError, SimplexError::new(ErrorCode::Storage, error))
+}
+
+async fn solve(problem: LinearProgram, caller: &Caller, solver_config: &SolverConfig) -> LinearProgramResponse
+{
+    // the key's size limits are checked here too since a stored run may have been saved by another key
+    if let Err(error) = caller.check(&problem)
+    {
+        return LinearProgramResponse::Error(error);
+    }
+
+    // the run is saved with its result once the solve is done, a client that goes away does not stop it and only the time
+    // limit bounds it
+    let options = SolveOptions { limits: solver_config.limits(None, None, None), ..SolveOptions::default() };
+
+    let span = tracing::Span::current();
+    match rocket::tokio::task::spawn_blocking(move || span.in_scope(|| crate::timed_solve(problem, &options))).await
+    {
+        Ok((response, wall_time)) =>
+        {
+            caller.record(&response, wall_time);
+            response
+        },
+        Err(error) => LinearProgramResponse::Error(SimplexError::internal(format!("The solver stopped unexpectedly: {}", error)))
+    }
+}
+
+// solves the problem right away and saves it together with its result
+#[post("/runs?<tags>", data = "<linear_program>")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0))]
+async fn create_run(linear_program: JsonBody<LinearProgram>, tags: Vec<String>, request_id: RequestId, caller: Caller, store: &State<Store>, solver_config: &State<SolverConfig>) -> StoreResult<Json<StoredRun>>
+{
+    if let Err(error) = caller.check(&linear_program)
+    {
+        return Err(RouteError::new(Status::PayloadTooLarge, error));
+    }
+
+    let problem = linear_program.into_inner();
+    let result = solve(problem.clone(), &caller, solver_config).await;
+
+    store.save(problem, Some(&result), tags, caller.name()).map(Json).map_err(internal_error)
+}
+
+// a run that belongs to another key is answered like one that does not exist
+fn owned_run(id: u64, caller: &Caller, store: &Store) -> StoreResult<Option<StoredRun>>
+{
+    match store.get(id).map_err(internal_error)?
+    {
+        Some(run) if caller.owns(run.owner.as_deref()) => Ok(Some(run)),
+        _ => Ok(None)
+    }
+}
+
+#[get("/runs?<tag>&<offset>&<limit>")]
+fn list_runs(tag: Option<&str>, offset: Option<usize>, limit: Option<usize>, caller: Caller, store: &State<Store>) -> StoreResult<Json<Vec<RunSummary>>>
+{
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    store.list(tag, caller.name(), offset.unwrap_or(0), limit).map(Json).map_err(internal_error)
+}
+
+#[get("/runs/<id>")]
+fn get_run(id: u64, caller: Caller, store: &State<Store>) -> StoreResult<Option<Json<StoredRun>>>
+{
+    owned_run(id, &caller, store).map(|run| run.map(Json))
+}
+
+#[post("/runs/<id>/solve")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0, run = id))]
+async fn resolve_run(id: u64, request_id: RequestId, caller: Caller, store: &State<Store>, solver_config: &State<SolverConfig>) -> StoreResult<Option<Json<StoredRun>>>
+{
+    let problem = match owned_run(id, &caller, store)?
+    {
+        Some(run) => run.problem,
+        None => return Ok(None)
+    };
+
+    let result = solve(problem, &caller, solver_config).await;
+
+    store.update_result(id, &result).map(|run| run.map(Json)).map_err(internal_error)
+}
+
+#[delete("/runs/<id>")]
+fn delete_run(id: u64, caller: Caller, store: &State<Store>) -> StoreResult<Option<Json<u64>>>
+{
+    if owned_run(id, &caller, store)?.is_none()
+    {
+        return Ok(None);
+    }
+
+    match store.delete(id).map_err(internal_error)?
+    {
+        true => Ok(Some(Json(id))),
+        false => Ok(None)
+    }
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![create_run, list_runs, get_run, resolve_run, delete_run]
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use rocket::http::Status;
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::Serialize;
+use rocket::tokio::sync::mpsc;
+use rocket::{Route, State};
+
+use crate::auth::Caller;
+use crate::config::SolverConfig;
+use crate::logging::RequestId;
+use crate::parse::JsonBody;
+use crate::row_arithmetic::{IterationEvent, LinearProgram, Row};
+use crate::v1::RouteError;
+use crate::LimitsQuery;
+
+// events waiting to be written to a slow client, past this the solver waits for the client
+const BUFFERED_EVENTS: usize = 64;
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct IterationUpdate<'a>
+{
+    #[serde(flatten)]
+    event: &'a IterationEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tableau: Option<&'a Vec<Row>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relative_costs: Option<&'a Row>
+}
+
+// an `iteration` event per pivot and a `result` event at the end, once the client goes away the solve is cancelled
+#[post("/stream?<tableau>&<limits..>", data = "<linear_program>")]
+fn stream_solve(linear_program: JsonBody<LinearProgram>, tableau: Option<bool>, limits: LimitsQuery, request_id: RequestId, caller: Caller,
+    solver_config: &State<SolverConfig>) -> Result<EventStream![], RouteError>
+{
+    // a problem too large for the key is turned away before any event is sent
+    if let Err(error) = caller.check(&linear_program)
+    {
+        return Err(RouteError::new(Status::PayloadTooLarge, error));
+    }
+
+    let include_tableau = tableau.unwrap_or(false);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let options = limits.options(solver_config, Some(cancelled.clone()));
+
+    let linear_program = linear_program.into_inner();
+    let (sender, mut receiver) = mpsc::channel::<Event>(BUFFERED_EVENTS);
+
+    // #[instrument] cannot wrap a function returning an EventStream, the solve is what needs the request id anyway
+    let span = tracing::info_span!("request", request_id = %request_id.0);
+    rocket::tokio::task::spawn_blocking(move ||
+    {
+        let _entered = span.enter();
+
+        let start = Instant::now();
+        let response = crate::solve_linear_program_observed(linear_program, &options, &mut |event, program|
+        {
+            let update = IterationUpdate
+            {
+                event: event,
+                tableau: if include_tableau { Some(&program.tableau) } else { None },
+                relative_costs: if include_tableau { Some(&program.relative_costs) } else { None }
+            };
+
+            if sender.blocking_send(Event::json(&update).event("iteration")).is_err()
+            {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+        caller.record(&response, start.elapsed());
+
+        let _ = sender.blocking_send(Event::json(&response).event("result"));
+    });
+
+    Ok(EventStream!
+    {
+        while let Some(event) = receiver.recv().await
+        {
+            yield event;
+        }
+    })
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![stream_solve]
+}
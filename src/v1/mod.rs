@@ -0,0 +1,150 @@
+use rocket::http::{Header, Status};
+use rocket::response::{self, status, Responder};
+use rocket::serde::Serialize;
+use rocket::serde::json::Json;
+use rocket::{Catcher, Request, Route, State};
+use schemars::JsonSchema;
+
+use crate::config::SolverConfig;
+use crate::error::{self, ErrorCode, SimplexError};
+use crate::parse::JsonBody;
+use crate::row_arithmetic::LinearProgram;
+use crate::{auth, cache, logging, store};
+use crate::{LimitReport, LinearProgramResponse, SolutionReport, SolveQuery};
+
+// every outcome the solver can reach is a 200, the status field says which one it was. a problem without a feasible
+// starting basis is turned away by the checks, so infeasible never gets this far
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "rocket::serde", tag = "status", rename_all = "snake_case")]
+pub enum SolveResult
+{
+    Optimal(SolutionReport),
+    Unbounded { message: String },
+    LimitReached(LimitReport)
+}
+
+pub type ApiResult<T> = Result<T, status::Custom<Json<SimplexError>>>;
+
+// problems that fail their checks are the client's to fix, anything the solver trips over on a checked problem is ours
+pub fn status_of(code: ErrorCode) -> Status
+{
+    match code
+    {
+        ErrorCode::InvalidJson | ErrorCode::InvalidOverride | ErrorCode::UnsupportedDimension | ErrorCode::EmptyTableau | ErrorCode::RowLengthMismatch
+            | ErrorCode::TooManyRows | ErrorCode::MissingIdentity | ErrorCode::InfeasibleSolution | ErrorCode::SolutionNotBasic
+            | ErrorCode::SolutionMismatch | ErrorCode::NonFiniteValue | ErrorCode::ZeroRow | ErrorCode::DuplicateRow
+            | ErrorCode::RankDeficient | ErrorCode::InvalidKey => Status::UnprocessableEntity,
+        ErrorCode::TooLarge => Status::PayloadTooLarge,
+        ErrorCode::NotAcceptable => Status::NotAcceptable,
+        ErrorCode::Unauthorized => Status::Unauthorized,
+        ErrorCode::Forbidden => Status::Forbidden,
+        ErrorCode::RateLimited => Status::TooManyRequests,
+        ErrorCode::NotFound => Status::NotFound,
+        ErrorCode::QueueFull | ErrorCode::ShuttingDown => Status::ServiceUnavailable,
+        _ => Status::InternalServerError
+    }
+}
+
+pub fn api_error(error: SimplexError) -> status::Custom<Json<SimplexError>>
+{
+    status::Custom(status_of(error.code), Json(error))
+}
+
+// the error of a route mounted both at / and under /v1. under /v1 it is answered like api_error, at / it keeps the
+// LinearProgramResponse body and the status the route has always answered with
+pub struct RouteError
+{
+    status: Status,
+    error: SimplexError
+}
+
+impl RouteError
+{
+    pub fn new(status: Status, error: SimplexError) -> Self
+    {
+        RouteError { status: status, error: error }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for RouteError
+{
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static>
+    {
+        if request.route().map_or(false, |route| route.uri.base().starts_with("/v1"))
+        {
+            api_error(self.error).respond_to(request)
+        }
+        else 
+        {
+            status::Custom(self.status, Json(LinearProgramResponse::Error(self.error))).respond_to(request)
+        }
+    }
+}
+
+#[derive(Responder)]
+pub struct SolveResponse
+{
+    inner: Json<SolveResult>,
+    cache: Header<'static>
+}
+
+// 422 when the problem fails its checks, 413 when it is too large for what was asked and 500 when the solver itself failed.
+// the status depends on the outcome so the answer cannot be streamed, a client that goes away does not stop the solve and
+// only the time limit bounds it
+#[post("/solve?<solve..>", data = "<linear_program>")]
+#[tracing::instrument(name = "request", skip_all, fields(request_id = %request_id.0))]
+async fn solve(linear_program: JsonBody<LinearProgram>, solve: SolveQuery, request_id: logging::RequestId, caller: auth::Caller, solver_config: &State<SolverConfig>,
+    store: &State<store::Store>, cache: &State<cache::ResultCache>) -> ApiResult<SolveResponse>
+{
+    caller.check(&linear_program).map_err(api_error)?;
+
+    let options = solve.options(solver_config, None);
+
+    let request = crate::SolveRequest::new(linear_program.into_inner(), options, cache);
+    let hit = request.hit();
+    let response = crate::solve_and_record(request, "v1", &caller, store, cache).await;
+
+    let result = match response
+    {
+        LinearProgramResponse::LinearProgram(report) => SolveResult::Optimal(report),
+        LinearProgramResponse::Unbound(message) => SolveResult::Unbounded { message: message },
+        LinearProgramResponse::LimitReached(report) => SolveResult::LimitReached(report),
+        LinearProgramResponse::Error(error) => return Err(api_error(error))
+    };
+
+    Ok(SolveResponse { inner: Json(result), cache: crate::cache_header(hit) })
+}
+
+// Rocket's own html error pages are swapped for json under /v1
+#[catch(default)]
+fn default_catcher(status: Status, request: &Request) -> status::Custom<Json<SimplexError>>
+{
+    if let Some(error) = error::guard_failure(request)
+    {
+        return status::Custom(status, Json(error));
+    }
+
+    let (code, message) = match status.code
+    {
+        400 => (ErrorCode::InvalidJson, String::from("The request could not be read")),
+        401 => (ErrorCode::Unauthorized, String::from("An API key is needed")),
+        403 => (ErrorCode::Forbidden, String::from("The API key is not allowed to do this")),
+        404 => (ErrorCode::NotFound, String::from("There is no such resource")),
+        413 => (ErrorCode::TooLarge, String::from("The request body is larger than the server accepts")),
+        422 => (ErrorCode::InvalidJson, String::from("The body is not a valid linear program")),
+        429 => (ErrorCode::RateLimited, String::from("Too many requests")),
+        _ => (ErrorCode::Internal, String::from(status.reason_lossy()))
+    };
+
+    status::Custom(status, Json(SimplexError::new(code, message)))
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![solve]
+}
+
+pub fn catchers() -> Vec<Catcher>
+{
+    catchers![default_catcher]
+}
@@ -0,0 +1,247 @@
+use rocket::http::Status;
+use rocket::serde::Serialize;
+use rocket::serde::json::Json;
+use rocket::Route;
+use schemars::JsonSchema;
+
+use crate::auth::Caller;
+use crate::error::{ErrorCode, SimplexError};
+use crate::geometry;
+use crate::parse::JsonBody;
+use crate::row_arithmetic::LinearProgram;
+use crate::v1::RouteError;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum Severity
+{
+    // perform_checks or the solver would turn the problem away
+    Error,
+    // the problem can be solved but probably is not what was meant
+    Warning
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Finding
+{
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub error: SimplexError
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ValidationReport
+{
+    pub valid: bool,
+    pub rows: usize,
+    pub columns: usize,
+    pub findings: Vec<Finding>
+}
+
+struct Findings(Vec<Finding>);
+
+impl Findings
+{
+    fn error(&mut self, error: SimplexError)
+    {
+        self.0.push(Finding { severity: Severity::Error, error: error });
+    }
+
+    fn warning(&mut self, error: SimplexError)
+    {
+        self.0.push(Finding { severity: Severity::Warning, error: error });
+    }
+}
+
+// unlike perform_checks this keeps going after a failed check and reports every offending entry
+pub fn validate(linear_program: &LinearProgram) -> ValidationReport
+{
+    let mut findings = Findings(Vec::new());
+    let rows = linear_program.tableau.len();
+    let columns = linear_program.tableau.first().map_or(0, |row| row.a_ij.len());
+
+    check_finite(linear_program, &mut findings);
+
+    if rows == 0
+    {
+        findings.error(SimplexError::new(ErrorCode::EmptyTableau, format!("The tableau has no rows")));
+        return report(findings, rows, columns);
+    }
+
+    let same_length = check_lengths(linear_program, columns, &mut findings);
+
+    if rows > columns
+    {
+        findings.error(SimplexError::new(ErrorCode::TooManyRows, format!("The linear program has more rows than columns: rows = {}, columns = {}", rows, columns))
+            .with_lengths(columns, rows));
+    }
+
+    check_identity(linear_program, &mut findings);
+    check_solution(linear_program, &mut findings);
+    check_rows(linear_program, &mut findings);
+
+    // elimination on rows of different lengths or with NaN in them says nothing useful
+    if same_length && linear_program.tableau.iter().all(|row| row.a_ij.iter().all(|x| x.is_finite()))
+    {
+        let matrix: Vec<Vec<f64>> = linear_program.tableau.iter().map(|row| row.a_ij.iter().map(|x| *x as f64).collect()).collect();
+        let rank = geometry::matrix_rank(matrix);
+
+        if rank < rows
+        {
+            findings.error(SimplexError::new(ErrorCode::RankDeficient, format!("The rows are linearly dependent: rank = {}, rows = {}", rank, rows))
+                .with_lengths(rows, rank));
+        }
+    }
+
+    report(findings, rows, columns)
+}
+
+fn report(findings: Findings, rows: usize, columns: usize) -> ValidationReport
+{
+    let valid = findings.0.iter().all(|finding| finding.severity != Severity::Error);
+    ValidationReport { valid: valid, rows: rows, columns: columns, findings: findings.0 }
+}
+
+fn check_finite(linear_program: &LinearProgram, findings: &mut Findings)
+{
+    let non_finite = |field: &str, value: f32| SimplexError::new(ErrorCode::NonFiniteValue, format!("{} is not a finite number", value))
+        .with_field(field).with_value(value);
+
+    for (row_index, row) in linear_program.tableau.iter().enumerate()
+    {
+        for (column, value) in row.a_ij.iter().enumerate().filter(|(_, x)| !x.is_finite())
+        {
+            findings.error(non_finite("tableau", *value).with_row(row_index).with_column(column));
+        }
+
+        if !row.b_i.is_finite()
+        {
+            findings.error(non_finite("b_i", row.b_i).with_row(row_index));
+        }
+    }
+
+    for (field, values) in [("costs", &linear_program.costs), ("relative_costs", &linear_program.relative_costs.a_ij), ("solution", &linear_program.solution)]
+    {
+        for (column, value) in values.iter().enumerate().filter(|(_, x)| !x.is_finite())
+        {
+            findings.error(non_finite(field, *value).with_column(column));
+        }
+    }
+}
+
+// every row, costs, relative_costs and solution need as many entries as the first row
+fn check_lengths(linear_program: &LinearProgram, columns: usize, findings: &mut Findings) -> bool
+{
+    let mut same_length = true;
+
+    for (row_index, row) in linear_program.tableau.iter().enumerate().filter(|(_, row)| row.a_ij.len() != columns)
+    {
+        same_length = false;
+        findings.error(SimplexError::new(ErrorCode::RowLengthMismatch, format!("Row {} has {} entries but the first row has {}", row_index, row.a_ij.len(), columns))
+            .with_row(row_index).with_lengths(columns, row.a_ij.len()));
+    }
+
+    for (field, length) in [("costs", linear_program.costs.len()), ("relative_costs", linear_program.relative_costs.a_ij.len()), ("solution", linear_program.solution.len())]
+    {
+        if length != columns
+        {
+            findings.error(SimplexError::new(ErrorCode::RowLengthMismatch, format!("{} has {} entries but the rows have {}", field, length, columns))
+                .with_field(field).with_lengths(columns, length));
+        }
+    }
+
+    same_length
+}
+
+fn check_identity(linear_program: &LinearProgram, findings: &mut Findings)
+{
+    let rows = linear_program.tableau.len();
+
+    for (row_index, row) in linear_program.tableau.iter().enumerate()
+    {
+        for (column, value) in row.a_ij.iter().enumerate().take(rows)
+        {
+            let expected = if column == row_index { 1.0 } else { 0.0 };
+            if *value != expected
+            {
+                findings.error(SimplexError::new(ErrorCode::MissingIdentity, format!("Entry ({}, {}) should be {} for the identity basis but is {}", row_index, column, expected, value))
+                    .with_row(row_index).with_column(column).with_value(*value));
+            }
+        }
+    }
+}
+
+fn check_solution(linear_program: &LinearProgram, findings: &mut Findings)
+{
+    let rows = linear_program.tableau.len();
+
+    for (column, value) in linear_program.solution.iter().enumerate()
+    {
+        if *value < 0.0
+        {
+            findings.error(SimplexError::new(ErrorCode::InfeasibleSolution, format!("Entry {} of the solution is negative", column))
+                .with_field("solution").with_column(column).with_value(*value));
+        }
+
+        if column >= rows && *value > 0.0
+        {
+            findings.error(SimplexError::new(ErrorCode::SolutionNotBasic, format!("Entry {} of the solution belongs to a non-basic column and should be 0", column))
+                .with_field("solution").with_column(column).with_value(*value));
+        }
+    }
+
+    for (row_index, row) in linear_program.tableau.iter().enumerate()
+    {
+        match linear_program.solution.get(row_index)
+        {
+            Some(value) if *value != row.b_i => findings.error(SimplexError::new(ErrorCode::SolutionMismatch,
+                format!("Entry {} of the solution is {} but b_i of row {} is {}", row_index, value, row_index, row.b_i))
+                .with_field("solution").with_row(row_index).with_column(row_index).with_value(*value)),
+            _ => ()
+        };
+    }
+}
+
+fn check_rows(linear_program: &LinearProgram, findings: &mut Findings)
+{
+    for (row_index, row) in linear_program.tableau.iter().enumerate()
+    {
+        if row.a_ij.iter().all(|x| *x == 0.0)
+        {
+            let zero_row = SimplexError::new(ErrorCode::ZeroRow, format!("Row {} has no non-zero entries", row_index)).with_row(row_index);
+
+            // 0 = b_i with b_i not 0 can never hold
+            if row.b_i != 0.0 { findings.error(zero_row.with_value(row.b_i)) } else { findings.warning(zero_row) }
+        }
+
+        if let Some(first) = linear_program.tableau.iter().take(row_index).position(|other| other.a_ij == row.a_ij && other.b_i == row.b_i)
+        {
+            findings.warning(SimplexError::new(ErrorCode::DuplicateRow, format!("Row {} is the same as row {}", row_index, first)).with_row(row_index));
+        }
+    }
+}
+
+// a 200 for every problem the key may send, whether it is valid is in the report. the rank and duplicate row checks
+// grow with the size of the problem, so they run on a blocking thread like a solve
+#[post("/validate", data = "<linear_program>")]
+async fn validate_program(linear_program: JsonBody<LinearProgram>, caller: Caller) -> Result<Json<ValidationReport>, RouteError>
+{
+    if let Err(error) = caller.check(&linear_program)
+    {
+        return Err(RouteError::new(Status::PayloadTooLarge, error));
+    }
+
+    let linear_program = linear_program.into_inner();
+    match rocket::tokio::task::spawn_blocking(move || validate(&linear_program)).await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(error) => Err(RouteError::new(Status::InternalServerError, SimplexError::internal(format!("The problem could not be validated: {}", error))))
+    }
+}
+
+pub fn routes() -> Vec<Route>
+{
+    routes![validate_program]
+}
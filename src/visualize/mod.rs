@@ -0,0 +1,306 @@
+use crate::error::{ErrorCode, SimplexError};
+use crate::geometry::{self, Halfspace, Vertex};
+use crate::row_arithmetic::{self, LinearProgram, SolveLimits};
+
+const SIZE: f64 = 500.0;
+const MARGIN: f64 = 40.0;
+const CONTOUR_LINES: usize = 7;
+
+// maps problem coordinates (already projected to the plane) onto the svg canvas
+struct Canvas
+{
+    min_x: f64,
+    max_x: f64,
+    min_y: f64,
+    max_y: f64
+}
+
+impl Canvas
+{
+    fn new(points: &Vec<(f64, f64)>) -> Self
+    {
+        let mut canvas = Canvas { min_x: f64::MAX, max_x: f64::MIN, min_y: f64::MAX, max_y: f64::MIN };
+
+        for (x, y) in points
+        {
+            canvas.min_x = canvas.min_x.min(*x);
+            canvas.max_x = canvas.max_x.max(*x);
+            canvas.min_y = canvas.min_y.min(*y);
+            canvas.max_y = canvas.max_y.max(*y);
+        }
+
+        if canvas.max_x - canvas.min_x <= 0.0 { canvas.max_x = canvas.min_x + 1.0; }
+        if canvas.max_y - canvas.min_y <= 0.0 { canvas.max_y = canvas.min_y + 1.0; }
+
+        canvas
+    }
+
+    // keeps the aspect ratio so that angles between constraints are not distorted
+    fn map(&self, point: (f64, f64)) -> (f64, f64)
+    {
+        let scale = (SIZE - 2.0 * MARGIN) / (self.max_x - self.min_x).max(self.max_y - self.min_y);
+        (MARGIN + (point.0 - self.min_x) * scale, SIZE - MARGIN - (point.1 - self.min_y) * scale)
+    }
+}
+
+// draws the feasible region of a problem with 2 or 3 structural variables together with the path preform_simplex takes
+pub fn feasible_region_svg(linear_program: &LinearProgram, limits: &SolveLimits) -> Result<String, SimplexError>
+{
+    let columns = geometry::structural_columns(linear_program);
+
+    if columns.len() != 2 && columns.len() != 3
+    {
+        return Err(SimplexError::new(ErrorCode::UnsupportedDimension, format!("Only problems with 2 or 3 variables besides the identity basis can be drawn: variables = {}", columns.len())));
+    }
+
+    // every choice of hyperplanes is tried, the same limit as for the vertices of a solve
+    let combinations = geometry::vertex_combinations(linear_program);
+    if combinations > geometry::MAX_VERTEX_COMBINATIONS
+    {
+        return Err(SimplexError::new(ErrorCode::TooLarge, format!("The problem is too large to draw: combinations = {}, maximum = {}", combinations, geometry::MAX_VERTEX_COMBINATIONS))
+            .with_lengths(geometry::MAX_VERTEX_COMBINATIONS, combinations));
+    }
+
+    let mut solved_program = linear_program.clone();
+    solved_program.relative_costs = solved_program.calculate_costs();
+    let (result, steps) = solved_program.preform_simplex_with_trace(limits);
+
+    if let row_arithmetic::SimplexResult::Error(error) = result
+    {
+        return Err(error);
+    }
+
+    let mut path: Vec<Vec<f64>> = Vec::new();
+    for step in &steps
+    {
+        let solution = step.solution();
+        let point: Vec<f64> = columns.iter().map(|column| solution[*column] as f64).collect();
+
+        // degenerate pivots stay on the same vertex
+        if path.last().map_or(true, |last| !geometry::same_point(last, &point))
+        {
+            path.push(point);
+        }
+    }
+
+    let halfspaces = geometry::halfspaces(linear_program);
+    let mut vertices = geometry::enumerate_vertices(&halfspaces, columns.len());
+
+    // anything unbounded gets cut off a bit past the furthest vertex we know about, only the corners the cut makes are
+    // new
+    let furthest = vertices.iter().map(|vertex| &vertex.point).chain(path.iter())
+        .flat_map(|point| point.iter()).fold(0.0_f64, |x, y| x.max(*y));
+    let bound = (furthest * 1.25).max(1.0);
+
+    let mut clipped_halfspaces = halfspaces.clone();
+    clipped_halfspaces.extend(geometry::bounding_halfspaces(columns.len(), bound));
+    geometry::add_vertices(&clipped_halfspaces, columns.len(), halfspaces.len(), &mut vertices);
+
+    let labels: Vec<String> = columns.iter().map(|column| format!("x{}", column + 1)).collect();
+    let objective = geometry::objective_in_structural_space(linear_program);
+
+    if columns.len() == 2
+    {
+        Ok(svg_2d(&vertices, &halfspaces, &path, &objective, bound, &labels))
+    }
+    else
+    {
+        Ok(svg_3d(&vertices, &halfspaces, &path, bound, &labels))
+    }
+}
+
+fn svg_header() -> String
+{
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n\
+<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"9\" refY=\"5\" markerWidth=\"6\" markerHeight=\"6\" orient=\"auto-start-reverse\">\
+<path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"crimson\"/></marker></defs>\n\
+<style>.region {{ fill: lightsteelblue; fill-opacity: 0.6; stroke: steelblue; stroke-width: 2; }} \
+.clipped {{ stroke-dasharray: 4 4; }} .contour {{ stroke: gray; stroke-width: 1; stroke-dasharray: 2 3; }} \
+.optimal {{ stroke: darkgreen; stroke-width: 2; stroke-dasharray: none; }} .axis {{ stroke: black; stroke-width: 1; }} \
+.vertex {{ fill: steelblue; }} .visited {{ fill: crimson; }} .path {{ fill: none; stroke: crimson; stroke-width: 2; }} \
+text {{ font-family: sans-serif; font-size: 12px; }}</style>\n", SIZE)
+}
+
+fn path_svg(points: &Vec<(f64, f64)>) -> String
+{
+    let mut svg = String::new();
+
+    if points.len() > 1
+    {
+        let coordinates: Vec<String> = points.iter().map(|(x, y)| format!("{:.2},{:.2}", x, y)).collect();
+        svg.push_str(&format!("<polyline class=\"path\" points=\"{}\" marker-end=\"url(#arrow)\"/>\n", coordinates.join(" ")));
+    }
+
+    for (index, (x, y)) in points.iter().enumerate()
+    {
+        svg.push_str(&format!("<circle class=\"visited\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"5\"/><text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n", x, y, x + 7.0, y - 7.0, index));
+    }
+
+    svg
+}
+
+fn is_artificial(vertex: &Vertex, halfspaces: &Vec<Halfspace>) -> bool
+{
+    vertex.tight.iter().any(|index| *index >= halfspaces.len())
+}
+
+fn svg_2d(vertices: &Vec<Vertex>, halfspaces: &Vec<Halfspace>, path: &Vec<Vec<f64>>, objective: &(Vec<f64>, f64), bound: f64, labels: &Vec<String>) -> String
+{
+    let canvas = Canvas::new(&vec![(0.0, 0.0), (bound, bound)]);
+    let mut svg = svg_header();
+
+    // a convex polygon can be ordered by the angle around its centroid
+    let centroid = vertices.iter().fold((0.0, 0.0), |sum, vertex| (sum.0 + vertex.point[0], sum.1 + vertex.point[1]));
+    let centroid = (centroid.0 / vertices.len().max(1) as f64, centroid.1 / vertices.len().max(1) as f64);
+    let mut polygon: Vec<&Vertex> = vertices.iter().collect();
+    polygon.sort_by(|x, y|
+    {
+        let angle_x = (x.point[1] - centroid.1).atan2(x.point[0] - centroid.0);
+        let angle_y = (y.point[1] - centroid.1).atan2(y.point[0] - centroid.0);
+        angle_x.partial_cmp(&angle_y).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let polygon_points: Vec<String> = polygon.iter().map(|vertex| canvas.map((vertex.point[0], vertex.point[1])))
+        .map(|(x, y)| format!("{:.2},{:.2}", x, y)).collect();
+    let clipped = if vertices.iter().any(|vertex| is_artificial(vertex, halfspaces)) { " clipped" } else { "" };
+    svg.push_str(&format!("<polygon class=\"region{}\" points=\"{}\"/>\n", clipped, polygon_points.join(" ")));
+
+    let (origin_x, origin_y) = canvas.map((0.0, 0.0));
+    let (end_x, _) = canvas.map((bound, 0.0));
+    let (_, end_y) = canvas.map((0.0, bound));
+    svg.push_str(&format!("<line class=\"axis\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/><text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n", origin_x, origin_y, end_x, origin_y, end_x - 10.0, origin_y + 20.0, labels[0]));
+    svg.push_str(&format!("<line class=\"axis\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/><text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n", origin_x, origin_y, origin_x, end_y, origin_x - 30.0, end_y + 10.0, labels[1]));
+
+    svg.push_str(&contours_svg(&canvas, objective, bound, path.last()));
+
+    for vertex in vertices.iter().filter(|vertex| !is_artificial(vertex, halfspaces))
+    {
+        let (x, y) = canvas.map((vertex.point[0], vertex.point[1]));
+        svg.push_str(&format!("<circle class=\"vertex\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\"/>\n", x, y));
+    }
+
+    let projected_path: Vec<(f64, f64)> = path.iter().map(|point| canvas.map((point[0], point[1]))).collect();
+    svg.push_str(&path_svg(&projected_path));
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// evenly spaced level sets of the objective across the drawn box, the one through the last vertex is highlighted
+fn contours_svg(canvas: &Canvas, objective: &(Vec<f64>, f64), bound: f64, last: Option<&Vec<f64>>) -> String
+{
+    let (gradient, constant) = objective;
+    let mut svg = String::new();
+
+    if gradient.iter().all(|x| x.abs() <= f64::EPSILON)
+    {
+        return svg;
+    }
+
+    let corners = [vec![0.0, 0.0], vec![bound, 0.0], vec![0.0, bound], vec![bound, bound]];
+    let values: Vec<f64> = corners.iter().map(|corner| geometry::dot(gradient, corner)).collect();
+    let lowest = values.iter().fold(f64::MAX, |x, y| x.min(*y));
+    let highest = values.iter().fold(f64::MIN, |x, y| x.max(*y));
+
+    let mut levels: Vec<(f64, &str)> = (1..=CONTOUR_LINES)
+        .map(|index| (lowest + (highest - lowest) * index as f64 / (CONTOUR_LINES + 1) as f64, "contour")).collect();
+    if let Some(point) = last
+    {
+        levels.push((geometry::dot(gradient, point), "contour optimal"));
+    }
+
+    for (level, class) in levels
+    {
+        let segment = clip_line_to_box(gradient, level, bound);
+        if segment.len() < 2
+        {
+            continue;
+        }
+
+        let (x1, y1) = canvas.map(segment[0]);
+        let (x2, y2) = canvas.map(segment[1]);
+        svg.push_str(&format!("<line class=\"{}\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/><text x=\"{:.2}\" y=\"{:.2}\" fill=\"gray\">{:.2}</text>\n",
+            class, x1, y1, x2, y2, x2 + 3.0, y2 - 3.0, level + constant));
+    }
+
+    svg
+}
+
+// intersections of gradient * x = level with the edges of [0, bound]^2
+fn clip_line_to_box(gradient: &Vec<f64>, level: f64, bound: f64) -> Vec<(f64, f64)>
+{
+    let mut points: Vec<(f64, f64)> = Vec::new();
+
+    for fixed in [0.0, bound]
+    {
+        if gradient[1].abs() > f64::EPSILON
+        {
+            let y = (level - gradient[0] * fixed) / gradient[1];
+            if y >= 0.0 && y <= bound { points.push((fixed, y)); }
+        }
+        if gradient[0].abs() > f64::EPSILON
+        {
+            let x = (level - gradient[1] * fixed) / gradient[0];
+            if x >= 0.0 && x <= bound { points.push((x, fixed)); }
+        }
+    }
+
+    points.dedup_by(|x, y| (x.0 - y.0).abs() <= 1e-9 && (x.1 - y.1).abs() <= 1e-9);
+    points
+}
+
+// isometric projection, z points up
+fn project(point: &Vec<f64>) -> (f64, f64)
+{
+    let angle = std::f64::consts::PI / 6.0;
+    ((point[0] - point[1]) * angle.cos(), point[2] - (point[0] + point[1]) * angle.sin())
+}
+
+// contour lines would be planes in 3 dimensions so only the polytope's edges, the axes and the path are drawn
+fn svg_3d(vertices: &Vec<Vertex>, halfspaces: &Vec<Halfspace>, path: &Vec<Vec<f64>>, bound: f64, labels: &Vec<String>) -> String
+{
+    let axes = [vec![bound, 0.0, 0.0], vec![0.0, bound, 0.0], vec![0.0, 0.0, bound]];
+    let mut projected: Vec<(f64, f64)> = vertices.iter().map(|vertex| project(&vertex.point)).collect();
+    projected.extend(axes.iter().map(project));
+    projected.push((0.0, 0.0));
+    let canvas = Canvas::new(&projected);
+
+    let mut svg = svg_header();
+
+    let origin = canvas.map(project(&vec![0.0, 0.0, 0.0]));
+    for (axis, label) in axes.iter().zip(labels.iter())
+    {
+        let (x, y) = canvas.map(project(axis));
+        svg.push_str(&format!("<line class=\"axis\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/><text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n", origin.0, origin.1, x, y, x + 5.0, y, label));
+    }
+
+    // two vertices share an edge when at least two of the same constraints are tight at both
+    for (index, vertex) in vertices.iter().enumerate()
+    {
+        for other in vertices.iter().skip(index + 1)
+        {
+            let shared: Vec<&usize> = vertex.tight.iter().filter(|x| other.tight.contains(x)).collect();
+            if shared.len() < 2
+            {
+                continue;
+            }
+
+            let clipped = if shared.iter().any(|x| **x >= halfspaces.len()) { " clipped" } else { "" };
+            let (x1, y1) = canvas.map(project(&vertex.point));
+            let (x2, y2) = canvas.map(project(&other.point));
+            svg.push_str(&format!("<line class=\"region{}\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n", clipped, x1, y1, x2, y2));
+        }
+    }
+
+    for vertex in vertices.iter().filter(|vertex| !is_artificial(vertex, halfspaces))
+    {
+        let (x, y) = canvas.map(project(&vertex.point));
+        svg.push_str(&format!("<circle class=\"vertex\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\"/>\n", x, y));
+    }
+
+    let projected_path: Vec<(f64, f64)> = path.iter().map(|point| canvas.map(project(point))).collect();
+    svg.push_str(&path_svg(&projected_path));
+
+    svg.push_str("</svg>\n");
+    svg
+}